@@ -0,0 +1,134 @@
+const SECONDS_PER_DAY: u32 = 24 * 60 * 60;
+
+/// Wall clock time of day
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct TimeOfDay {
+    pub hours: u8,
+    pub minutes: u8,
+    pub seconds: u8,
+}
+
+impl TimeOfDay {
+    pub fn new(hours: u8, minutes: u8, seconds: u8) -> Self {
+        Self::from_seconds(hours as u32 * 3600 + minutes as u32 * 60 + seconds as u32)
+    }
+    /// Time at `seconds` past midnight, wrapping around into the next day.
+    pub fn from_seconds(seconds: u32) -> Self {
+        let seconds = seconds % SECONDS_PER_DAY;
+        Self {
+            hours: (seconds / 3600) as u8,
+            minutes: (seconds / 60 % 60) as u8,
+            seconds: (seconds % 60) as u8,
+        }
+    }
+    /// Seconds past midnight
+    pub fn seconds_since_midnight(self) -> u32 {
+        self.hours as u32 * 3600 + self.minutes as u32 * 60 + self.seconds as u32
+    }
+    /// Move the time forwards or backwards, wrapping around midnight.
+    pub fn add_seconds(self, delta: i32) -> Self {
+        let day = SECONDS_PER_DAY as i32;
+        let seconds = (self.seconds_since_midnight() as i32 + delta).rem_euclid(day);
+        Self::from_seconds(seconds as u32)
+    }
+}
+
+/// Source of wall clock time, such as the `Rtc`
+pub trait TimeSource {
+    fn now(&mut self) -> TimeOfDay;
+    fn set(&mut self, time: TimeOfDay);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        effect::{Clock, Effect, Readout},
+        Color, Lantern,
+    };
+
+    /// Time source that stands still until it is set
+    struct MockSource {
+        time: TimeOfDay,
+        writes: usize,
+    }
+
+    impl TimeSource for MockSource {
+        fn now(&mut self) -> TimeOfDay {
+            self.time
+        }
+        fn set(&mut self, time: TimeOfDay) {
+            self.time = time;
+            self.writes += 1;
+        }
+    }
+
+    /// What the tick task does with the effect and the RTC
+    fn sync(clock: &mut Clock<Lantern>, source: &mut MockSource) {
+        if let Some(time) = clock.sync_time(source.now()) {
+            source.set(time);
+        }
+    }
+
+    #[test]
+    fn new_wraps_past_midnight() {
+        let t = TimeOfDay::new(12, 34, 56);
+        assert_eq!((t.hours, t.minutes, t.seconds), (12, 34, 56));
+        assert_eq!(TimeOfDay::new(24, 0, 0), TimeOfDay::new(0, 0, 0));
+        assert_eq!(TimeOfDay::new(23, 59, 60), TimeOfDay::new(0, 0, 0));
+        assert_eq!(TimeOfDay::new(25, 61, 0), TimeOfDay::new(2, 1, 0));
+    }
+
+    #[test]
+    fn from_seconds_wraps_at_a_day() {
+        assert_eq!(TimeOfDay::from_seconds(0), TimeOfDay::new(0, 0, 0));
+        assert_eq!(TimeOfDay::from_seconds(86_399), TimeOfDay::new(23, 59, 59));
+        assert_eq!(TimeOfDay::from_seconds(86_400), TimeOfDay::new(0, 0, 0));
+        assert_eq!(TimeOfDay::from_seconds(90_061), TimeOfDay::new(1, 1, 1));
+    }
+
+    #[test]
+    fn add_seconds_wraps_both_ways() {
+        let late = TimeOfDay::new(23, 59, 30);
+        assert_eq!(late.add_seconds(30), TimeOfDay::new(0, 0, 0));
+        assert_eq!(late.add_seconds(3600), TimeOfDay::new(0, 59, 30));
+        let early = TimeOfDay::new(0, 0, 10);
+        assert_eq!(early.add_seconds(-20), TimeOfDay::new(23, 59, 50));
+        assert_eq!(early.add_seconds(-86_400), early);
+    }
+
+    #[test]
+    fn clock_follows_the_source() {
+        let mut clock: Clock<Lantern> = Clock::default();
+        let mut source = MockSource {
+            time: TimeOfDay::new(8, 15, 0),
+            writes: 0,
+        };
+        sync(&mut clock, &mut source);
+        sync(&mut clock, &mut source);
+        assert_eq!(source.writes, 0);
+        assert_eq!(source.time, TimeOfDay::new(8, 15, 0));
+    }
+
+    #[test]
+    fn clock_writes_back_adjustments() {
+        let mut clock: Clock<Lantern> = Clock::default();
+        let mut source = MockSource {
+            time: TimeOfDay::new(23, 30, 0),
+            writes: 0,
+        };
+        let mut color = Color::new(50.0, 50.0, 0.0);
+        let mut out = Readout::new();
+        sync(&mut clock, &mut source);
+        // Knob 3: click to set the hours, then turn forward past midnight
+        clock.click(&mut color, &mut out);
+        clock.rotate_cw(&mut color, &mut out);
+        assert_eq!(out.as_str(), "Time: 00:30");
+        sync(&mut clock, &mut source);
+        assert_eq!(source.writes, 1);
+        assert_eq!(source.time, TimeOfDay::new(0, 30, 0));
+        // Once written back the clock follows the source again
+        sync(&mut clock, &mut source);
+        assert_eq!(source.writes, 1);
+    }
+}
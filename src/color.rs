@@ -1,3 +1,4 @@
+use num_traits::Float;
 use palette::{rgb::Srgb, white_point::D65, Component, ConvertFrom, Lch, Pixel};
 
 pub type Color = Lch<D65, f32>;
@@ -12,3 +13,28 @@ pub fn lch_to_rgb(lch: Color) -> [u8; 3] {
 pub fn lch_color<T: Into<f32>>(l: T, chroma: T, hue: T) -> Color {
     Color::new(l.into(), chroma.into(), hue.into())
 }
+
+/// Approximate colour of a black body radiator at `kelvin`, valid from about 1000K to 40000K.
+// From https://tannerhelland.com/2012/09/18/convert-temperature-rgb-algorithm-code.html
+pub fn blackbody(kelvin: f32) -> Color {
+    let t = kelvin / 100.0;
+    let r = if t <= 66.0 {
+        255.0
+    } else {
+        329.698_73 * (t - 60.0).powf(-0.133_204_76)
+    };
+    let g = if t <= 66.0 {
+        99.470_8 * t.ln() - 161.119_57
+    } else {
+        288.122_17 * (t - 60.0).powf(-0.075_514_85)
+    };
+    let b = if t >= 66.0 {
+        255.0
+    } else if t <= 19.0 {
+        0.0
+    } else {
+        138.517_73 * (t - 10.0).ln() - 305.044_8
+    };
+    let norm = |c: f32| c.clamp(0.0, 255.0) / 255.0;
+    Color::convert_from(Srgb::new(norm(r), norm(g), norm(b)))
+}
@@ -0,0 +1,176 @@
+use crate::{
+    effect::{particle::Particle, Readout, TICKS_PER_SECOND},
+    math::noise::Simplex,
+    Color, Effect, FaceType, PixelIndexable,
+};
+use core::{f32::consts::PI, marker::PhantomData};
+use num_traits::Float;
+use palette::Mix;
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+// Hues of the lower and upper edges of the curtains
+const PALETTES: [(f32, f32); 4] = [
+    (140.0, 300.0),
+    (140.0, 30.0),
+    (190.0, 270.0),
+    (350.0, 300.0),
+];
+const NUM_METEORS: usize = 2;
+// One in this many pixels of the top face is a star
+const STAR_SPACING: usize = 4;
+
+#[derive(Clone, Copy, Debug)]
+pub enum Control {
+    Width,
+    Colors,
+    Speed,
+}
+
+impl Control {
+    pub fn next(self) -> Self {
+        use Control::*;
+        match self {
+            Width => Colors,
+            Colors => Speed,
+            Speed => Width,
+        }
+    }
+}
+
+pub struct Aurora<T: PixelIndexable> {
+    // Time in seconds, scaled by speed
+    time: f32,
+    speed: f32,
+    // Number of curtain folds around the sides, roughly
+    width: f32,
+    palette: usize,
+    meteors: [Particle; NUM_METEORS],
+    control: Control,
+    noise: Simplex,
+    rng: SmallRng,
+    _pd: PhantomData<T>,
+}
+
+impl<T: PixelIndexable> Aurora<T> {
+    pub fn new(width: f32, speed: f32, palette: usize) -> Self {
+        Self {
+            time: 0.0,
+            speed,
+            width,
+            palette: palette % PALETTES.len(),
+            meteors: [Particle::dead(); NUM_METEORS],
+            control: Control::Width,
+            noise: Simplex::new(137),
+            rng: SmallRng::seed_from_u64(1234),
+            _pd: PhantomData,
+        }
+    }
+
+    /// Brightness of the curtains at a point on the sides, from 0.0 to 1.0
+    fn curtain(&self, dir: f32, height: f32) -> f32 {
+        let t = self.time;
+        // Curtains sway side to side, more at the top than the bottom
+        let sway = 0.05 * height * self.noise.noise_2d(height * 2.0, t * 0.5);
+        // Sample around a circle so the noise joins up all the way around the sides
+        let (s, c) = ((dir + sway) * 2.0 * PI).sin_cos();
+        let r = self.width / (2.0 * PI);
+        let fold = (self.noise.noise_3d(s * r, c * r, t * 0.3) + 1.0) / 2.0;
+        // Bright rays ripple up the curtains
+        let ripple = 0.6 + 0.4 * (height * 12.0 - t * 3.0 + fold * 4.0).sin();
+        // Curtains are brightest along their lower edge and thin out towards the top
+        let profile = 1.0 - 0.7 * height;
+        (fold * fold * ripple * profile * 1.8).min(1.0)
+    }
+
+    fn spawn_meteor(&mut self) {
+        let rng = &mut self.rng;
+        if let Some(p) = self.meteors.iter_mut().find(|p| !p.is_alive()) {
+            let ddir = rng.gen_range(-0.01, 0.01);
+            let color = Color::new(90.0, 10.0, 60.0);
+            *p = Particle::new(rng.gen_range(0.0, 1.0), 1.0, ddir, -0.06, color);
+        }
+    }
+
+    fn adjust(&mut self, up: bool, out: &mut Readout) {
+        match self.control {
+            Control::Width => {
+                self.width = (self.width + if up { 0.5 } else { -0.5 }).clamp(1.0, 12.0);
+                out.show(format_args!("Width: {}", self.width));
+            }
+            Control::Colors => {
+                self.palette =
+                    (self.palette + if up { 1 } else { PALETTES.len() - 1 }) % PALETTES.len();
+                out.show(format_args!("Colors: {}", self.palette));
+            }
+            Control::Speed => {
+                self.speed = (self.speed * if up { 1.2 } else { 1.0 / 1.2 }).clamp(0.05, 5.0);
+                out.show(format_args!("Speed: {}", self.speed));
+            }
+        }
+    }
+}
+
+impl<T: PixelIndexable> Default for Aurora<T> {
+    fn default() -> Self {
+        Self::new(4.0, 1.0, 0)
+    }
+}
+
+impl<T: PixelIndexable> Effect<T> for Aurora<T> {
+    fn tick(&mut self, _color: &mut Color) {
+        self.time += self.speed / TICKS_PER_SECOND as f32;
+        if self.rng.gen_ratio(1, 100) {
+            self.spawn_meteor();
+        }
+        for p in self.meteors.iter_mut().filter(|p| p.is_alive()) {
+            p.tick(0.0, 0.0);
+            // Meteors only streak across the sky on the top face
+            if p.height < 5.0 / 8.0 {
+                p.kill();
+            }
+        }
+    }
+
+    fn render(&self, _color: Color, model: &mut T) {
+        let (lower, upper) = PALETTES[self.palette];
+        let (lower, upper) = (Color::new(70.0, 90.0, lower), Color::new(40.0, 80.0, upper));
+        let night = Color::new(2.0, 10.0, 260.0);
+        for idx in model.iter_pixels() {
+            let (dir, height) = idx.as_spherical();
+            *model.get_mut(idx) = match idx.face_type() {
+                FaceType::Side => {
+                    let h = height * 7.0 / 4.0;
+                    night.mix(&lower.mix(&upper, h), self.curtain(dir, h))
+                }
+                FaceType::Top => {
+                    let i = idx.usize();
+                    if i % STAR_SPACING == 0 {
+                        let twinkle = self.noise.noise_2d(i as f32, self.time * 3.0);
+                        Color {
+                            l: 30.0 + 20.0 * twinkle,
+                            ..night
+                        }
+                    } else {
+                        night
+                    }
+                }
+            };
+        }
+        for p in &self.meteors {
+            p.render(model);
+        }
+    }
+
+    fn rotate_cw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.adjust(true, out);
+    }
+
+    fn rotate_ccw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.adjust(false, out);
+    }
+
+    fn click(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.control = self.control.next();
+        out.show(format_args!("Set: {:?}", self.control));
+    }
+}
@@ -0,0 +1,318 @@
+use crate::{
+    effect::{particle::Heading, Readout},
+    pixelindex::PixelIterator,
+    Color, Effect, FaceType, PixelIndex, PixelIndexable,
+};
+use core::marker::PhantomData;
+use generic_array::{ArrayLength, GenericArray};
+use palette::Hue;
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+const MOORE: [Heading; 8] = [
+    Heading::Up,
+    Heading::Down,
+    Heading::Left,
+    Heading::Right,
+    Heading::UpLeft,
+    Heading::UpRight,
+    Heading::DownLeft,
+    Heading::DownRight,
+];
+// Generations a pattern may sit still or blink before it is reseeded
+const STAGNATION_LIMIT: u32 = 20;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Rule {
+    /// Life-like rule over the eight surrounding cells, as bitmasks of neighbour counts
+    Life { birth: u16, survive: u16 },
+    /// Wolfram elementary rule, run around the top row of the sides and scrolled down
+    Elementary(u8),
+    /// Each cell advances to the next of `states` when `threshold` neighbours are already there
+    Cyclic { states: u8, threshold: u8 },
+}
+
+impl Rule {
+    /// Life-like rule from the B/S notation ending `name`, e.g. "HighLife B36/S23".  Bad
+    /// notation in a const fails the build.
+    pub const fn life(name: &str) -> Self {
+        let bytes = name.as_bytes();
+        // The notation is the last word
+        let mut i = bytes.len();
+        while i > 0 && bytes[i - 1] != b' ' {
+            i -= 1;
+        }
+        assert!(i < bytes.len() && bytes[i] == b'B', "expected B/S notation");
+        i += 1;
+        let mut birth = 0;
+        while i < bytes.len() && bytes[i] != b'/' {
+            birth |= Self::count_bit(bytes[i]);
+            i += 1;
+        }
+        assert!(
+            i + 1 < bytes.len() && bytes[i + 1] == b'S',
+            "expected B/S notation"
+        );
+        i += 2;
+        let mut survive = 0;
+        while i < bytes.len() {
+            survive |= Self::count_bit(bytes[i]);
+            i += 1;
+        }
+        Rule::Life { birth, survive }
+    }
+
+    const fn count_bit(digit: u8) -> u16 {
+        assert!(
+            digit >= b'0' && digit <= b'8',
+            "neighbour counts run from 0 to 8"
+        );
+        1 << (digit - b'0')
+    }
+}
+
+/// Preset for a life-like rule, whose masks come from the notation in its name
+const fn life(name: &'static str) -> (&'static str, Rule) {
+    (name, Rule::life(name))
+}
+
+const RULES: [(&str, Rule); 8] = [
+    life("Life B3/S23"),
+    life("HighLife B36/S23"),
+    life("Day & Night B3678/S34678"),
+    life("Seeds B2/S"),
+    ("Rule 30", Rule::Elementary(30)),
+    ("Rule 110", Rule::Elementary(110)),
+    (
+        "Cyclic",
+        Rule::Cyclic {
+            states: 6,
+            threshold: 1,
+        },
+    ),
+    (
+        "Cyclic spirals",
+        Rule::Cyclic {
+            states: 4,
+            threshold: 2,
+        },
+    ),
+];
+
+pub struct Automata<T: PixelIndexable>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    // State of each cell, 0.0 for dead and counting up
+    cells: GenericArray<f32, T::SIZE>,
+    rule: Rule,
+    preset: usize,
+    // Ticks between generations
+    period: u32,
+    ticks: u32,
+    // Fingerprints of the last two generations, to spot still lifes and blinkers
+    history: [u32; 2],
+    stagnant: u32,
+    rng: SmallRng,
+    _pd: PhantomData<T>,
+}
+
+impl<T: PixelIndexable> Automata<T>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    pub fn new(preset: usize, period: u32) -> Self {
+        let mut rv = Self {
+            cells: GenericArray::default(),
+            rule: RULES[preset % RULES.len()].1,
+            preset: preset % RULES.len(),
+            period,
+            ticks: 0,
+            history: [0; 2],
+            stagnant: 0,
+            rng: SmallRng::seed_from_u64(1234),
+            _pd: PhantomData,
+        };
+        rv.reseed();
+        rv
+    }
+
+    fn reseed(&mut self) {
+        let rule = self.rule;
+        let rng = &mut self.rng;
+        let px_iter: PixelIterator<T> = PixelIterator::all();
+        for idx in px_iter {
+            self.cells[idx.usize()] = match rule {
+                Rule::Life { .. } => rng.gen_range(0, 3) / 2,
+                Rule::Elementary(_) => match idx.face_type() {
+                    FaceType::Side => rng.gen_range(0, 2),
+                    FaceType::Top => 0,
+                },
+                Rule::Cyclic { states, .. } => rng.gen_range(0, states),
+            } as f32;
+        }
+        self.stagnant = 0;
+    }
+
+    fn select(&mut self, preset: usize, out: &mut Readout) {
+        self.preset = preset % RULES.len();
+        self.rule = RULES[self.preset].1;
+        out.show(format_args!("{}", RULES[self.preset].0));
+        self.reseed();
+    }
+
+    fn state(&self, idx: PixelIndex<T>) -> u8 {
+        self.cells[idx.usize()] as u8
+    }
+
+    fn count(&self, idx: PixelIndex<T>, state: u8) -> u32 {
+        MOORE
+            .iter()
+            .filter_map(|h| h.step(idx))
+            .filter(|&n| self.state(n) == state)
+            .count() as u32
+    }
+
+    fn next_state(&self, idx: PixelIndex<T>) -> u8 {
+        let state = self.state(idx);
+        match self.rule {
+            Rule::Life { birth, survive } => {
+                let mask = if state > 0 { survive } else { birth };
+                (mask >> self.count(idx, 1) & 1) as u8
+            }
+            Rule::Elementary(rule) => match idx.up() {
+                // The top row of the sides runs the rule, and every other row takes the
+                // value from the row above it
+                Some(up) => match up.face_type() {
+                    FaceType::Side => self.state(up),
+                    FaceType::Top => {
+                        let bit = |n: Option<PixelIndex<T>>| n.map_or(0, |n| self.state(n));
+                        let pattern = bit(idx.left()) << 2 | state << 1 | bit(idx.right());
+                        rule >> pattern & 1
+                    }
+                },
+                None => 0,
+            },
+            Rule::Cyclic { states, threshold } => {
+                let next = (state + 1) % states;
+                if self.count(idx, next) >= threshold as u32 {
+                    next
+                } else {
+                    state
+                }
+            }
+        }
+    }
+
+    fn step(&mut self) {
+        let mut next: GenericArray<f32, T::SIZE> = GenericArray::default();
+        let px_iter: PixelIterator<T> = PixelIterator::all();
+        for idx in px_iter {
+            next[idx.usize()] = match idx.face_type() {
+                FaceType::Top if matches!(self.rule, Rule::Elementary(_)) => 0.0,
+                _ => self.next_state(idx) as f32,
+            };
+        }
+        self.cells = next;
+        // FNV-1a over the cell states
+        let fingerprint = self.cells.iter().fold(0x811c_9dc5u32, |hash, &cell| {
+            (hash ^ cell as u32).wrapping_mul(0x0100_0193)
+        });
+        let alive = self.cells.iter().any(|&cell| cell > 0.0);
+        if !alive || self.history.contains(&fingerprint) {
+            self.stagnant += 1;
+        } else {
+            self.stagnant = 0;
+        }
+        self.history = [fingerprint, self.history[0]];
+        if self.stagnant > STAGNATION_LIMIT {
+            self.reseed();
+        }
+    }
+}
+
+impl<T: PixelIndexable> Default for Automata<T>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    fn default() -> Self {
+        Self::new(0, 3)
+    }
+}
+
+impl<T: PixelIndexable> Effect<T> for Automata<T>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    fn tick(&mut self, _color: &mut Color) {
+        self.ticks += 1;
+        if self.ticks >= self.period {
+            self.ticks = 0;
+            self.step();
+        }
+    }
+
+    fn render(&self, color: Color, model: &mut T) {
+        let dead = Color {
+            l: color.l * 0.05,
+            ..color
+        };
+        for idx in model.iter_pixels() {
+            let state = self.state(idx);
+            *model.get_mut(idx) = match self.rule {
+                Rule::Cyclic { states, .. } => {
+                    color.shift_hue(360.0 * state as f32 / states as f32)
+                }
+                _ if state > 0 => color,
+                _ => dead,
+            };
+        }
+    }
+
+    fn rotate_cw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.select(self.preset + 1, out);
+    }
+
+    fn rotate_ccw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.select(self.preset + RULES.len() - 1, out);
+    }
+
+    fn click(&mut self, _color: &mut Color, _out: &mut Readout) {
+        self.reseed();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn life_notation_sets_count_bits() {
+        assert_eq!(
+            Rule::life("Life B3/S23"),
+            Rule::Life {
+                birth: 1 << 3,
+                survive: 1 << 2 | 1 << 3,
+            }
+        );
+        assert_eq!(
+            Rule::life("Day & Night B3678/S34678"),
+            Rule::Life {
+                birth: 1 << 3 | 1 << 6 | 1 << 7 | 1 << 8,
+                survive: 1 << 3 | 1 << 4 | 1 << 6 | 1 << 7 | 1 << 8,
+            }
+        );
+        assert_eq!(
+            Rule::life("Seeds B2/S"),
+            Rule::Life {
+                birth: 1 << 2,
+                survive: 0,
+            }
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn life_notation_rejects_nine_neighbours() {
+        Rule::life("B9/S23");
+    }
+}
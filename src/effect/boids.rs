@@ -0,0 +1,169 @@
+use crate::{effect::Readout, math::Vec3, Color, Effect, PixelIndexable};
+use core::marker::PhantomData;
+use palette::{Hue, Mix};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+pub const NUM_BOIDS: usize = 12;
+
+#[derive(Clone, Copy)]
+pub enum Control {
+    Separation,
+    Alignment,
+    Cohesion,
+}
+
+impl Control {
+    pub fn next(self) -> Self {
+        use Control::*;
+        match self {
+            Separation => Alignment,
+            Alignment => Cohesion,
+            Cohesion => Separation,
+        }
+    }
+}
+
+/// A bird flying over the surface, kept on the unit sphere so that it wraps smoothly around
+/// the sides and over the top face.
+#[derive(Copy, Clone, Debug, Default)]
+struct Boid {
+    pos: Vec3,
+    vel: Vec3,
+}
+
+pub struct Boids<T: PixelIndexable, const N: usize> {
+    separation: f32,
+    alignment: f32,
+    cohesion: f32,
+    // Distance within which other boids are flockmates
+    sight: f32,
+    // Distance within which boids steer apart
+    personal_space: f32,
+    min_speed: f32,
+    max_speed: f32,
+    fade: f32,
+    control: Control,
+    boids: [Boid; N],
+    _pd: PhantomData<T>,
+}
+
+impl<T: PixelIndexable, const N: usize> Boids<T, N> {
+    pub fn new(separation: f32, alignment: f32, cohesion: f32) -> Self {
+        let mut rng = SmallRng::seed_from_u64(1234);
+        let mut boids = [Boid::default(); N];
+        for boid in boids.iter_mut() {
+            boid.pos = Vec3::from_spherical(rng.gen_range(0.0, 1.0), rng.gen_range(0.1, 0.9));
+            let vel = Vec3::new(
+                rng.gen_range(-1.0, 1.0),
+                rng.gen_range(-1.0, 1.0),
+                rng.gen_range(-1.0, 1.0),
+            );
+            boid.vel = vel.tangent(boid.pos).normalize() * 0.03;
+        }
+        Self {
+            separation,
+            alignment,
+            cohesion,
+            sight: 0.6,
+            personal_space: 0.2,
+            min_speed: 0.02,
+            max_speed: 0.05,
+            fade: 0.3,
+            control: Control::Separation,
+            boids,
+            _pd: PhantomData,
+        }
+    }
+
+    fn steer(&self, i: usize) -> Vec3 {
+        let me = self.boids[i];
+        let mut separate = Vec3::zero();
+        let mut heading = Vec3::zero();
+        let mut centre = Vec3::zero();
+        let mut mates = 0;
+        for (j, other) in self.boids.iter().enumerate() {
+            if i == j {
+                continue;
+            }
+            let offset = me.pos - other.pos;
+            let dist = offset.length();
+            if dist < self.sight {
+                heading += other.vel;
+                centre += other.pos;
+                mates += 1;
+                if dist < self.personal_space && dist > 0.0 {
+                    separate += offset * (1.0 / (dist * dist));
+                }
+            }
+        }
+        let mut steer = separate * (self.separation * 0.001);
+        if mates > 0 {
+            let n = 1.0 / mates as f32;
+            steer += (heading * n - me.vel) * (self.alignment * 0.1);
+            steer += (centre * n - me.pos) * (self.cohesion * 0.01);
+        }
+        // The lantern has no bottom face, so keep the flock above the bottom of the sides
+        if me.pos.z < 0.15 {
+            steer += Vec3::new(0.0, 0.0, 0.01);
+        }
+        steer
+    }
+
+    fn adjust(&mut self, factor: f32, out: &mut Readout) {
+        let (name, weight) = match self.control {
+            Control::Separation => ("Separation", &mut self.separation),
+            Control::Alignment => ("Alignment", &mut self.alignment),
+            Control::Cohesion => ("Cohesion", &mut self.cohesion),
+        };
+        *weight = (*weight * factor).clamp(0.01, 10.0);
+        out.show(format_args!("{}: {}", name, weight));
+    }
+}
+
+impl<T: PixelIndexable, const N: usize> Default for Boids<T, N> {
+    fn default() -> Self {
+        Self::new(1.0, 1.0, 1.0)
+    }
+}
+
+impl<T: PixelIndexable, const N: usize> Effect<T> for Boids<T, N> {
+    fn tick(&mut self, _color: &mut Color) {
+        let mut steer = [Vec3::zero(); N];
+        for (i, s) in steer.iter_mut().enumerate() {
+            *s = self.steer(i);
+        }
+        for (boid, steer) in self.boids.iter_mut().zip(steer.iter()) {
+            let mut vel = (boid.vel + *steer).tangent(boid.pos);
+            let speed = vel.length();
+            if speed > self.max_speed {
+                vel = vel * (self.max_speed / speed);
+            } else if speed < self.min_speed {
+                vel = vel.normalize() * self.min_speed;
+            }
+            boid.pos = (boid.pos + vel).normalize();
+            // Keep the velocity tangent to the surface at the new position
+            boid.vel = vel.tangent(boid.pos);
+        }
+    }
+
+    fn render(&self, color: Color, model: &mut T) {
+        model.map_pixels(|_idx, px| px.mix(&Color::new(0.0, 0.0, px.hue), self.fade));
+        for (i, boid) in self.boids.iter().enumerate() {
+            let (dir, height) = boid.pos.to_spherical();
+            let hue = 40.0 * i as f32 / N as f32;
+            *model.get_spherical_mut(dir, height.max(0.0)) = color.shift_hue(hue);
+        }
+    }
+
+    fn rotate_cw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.adjust(1.2, out);
+    }
+
+    fn rotate_ccw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.adjust(1.0 / 1.2, out);
+    }
+
+    fn click(&mut self, _color: &mut Color, _out: &mut Readout) {
+        self.control = self.control.next();
+    }
+}
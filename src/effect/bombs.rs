@@ -0,0 +1,163 @@
+use crate::{
+    effect::{
+        particle::{Crawler, Heading, Particle},
+        Readout,
+    },
+    Color, Effect, PixelIndexable,
+};
+use core::marker::PhantomData;
+use palette::{Hue, Mix};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+const NUM_BOMBS: usize = 4;
+const NUM_SHRAPNEL: usize = 20;
+const BLAST: [Heading; 5] = [
+    Heading::Left,
+    Heading::UpLeft,
+    Heading::Up,
+    Heading::UpRight,
+    Heading::Right,
+];
+
+#[derive(Clone, Copy)]
+pub enum Control {
+    Density,
+    Radius,
+}
+
+impl Control {
+    pub fn next(self) -> Self {
+        use Control::*;
+        match self {
+            Density => Radius,
+            Radius => Density,
+        }
+    }
+}
+
+pub struct Bombs<T: PixelIndexable> {
+    // Chance per tick of dropping a new bomb
+    density: f32,
+    // Number of pixels the blast travels from the impact
+    radius: u8,
+    gravity: f32,
+    fade: f32,
+    control: Control,
+    bombs: [Particle; NUM_BOMBS],
+    shrapnel: [Option<Crawler<T>>; NUM_SHRAPNEL],
+    rng: SmallRng,
+    _pd: PhantomData<T>,
+}
+
+impl<T: PixelIndexable> Bombs<T> {
+    pub fn new(density: f32, radius: u8, gravity: f32, fade: f32) -> Self {
+        Self {
+            density,
+            radius,
+            gravity,
+            fade,
+            control: Control::Density,
+            bombs: [Particle::dead(); NUM_BOMBS],
+            shrapnel: [None; NUM_SHRAPNEL],
+            rng: SmallRng::seed_from_u64(1234),
+            _pd: PhantomData,
+        }
+    }
+
+    fn drop_bomb(&mut self, color: Color) {
+        if let Some(bomb) = self.bombs.iter_mut().find(|b| !b.is_alive()) {
+            let dir = self.rng.gen_range(0.0, 1.0);
+            let height = self.rng.gen_range(5.0 / 8.0, 1.0);
+            *bomb = Particle::new(dir, height, 0.0, 0.0, color);
+        }
+    }
+
+    fn explode(&mut self, dir: f32, color: Color) {
+        let idx = T::cylindrical_to_index(dir, 0.0);
+        let mut slots = self.shrapnel.iter_mut().filter(|s| match s {
+            Some(c) => !c.is_alive(),
+            None => true,
+        });
+        for &heading in BLAST.iter() {
+            match slots.next() {
+                Some(slot) => *slot = Some(Crawler::new(idx, heading, self.radius, color)),
+                None => break,
+            }
+        }
+    }
+}
+
+impl<T: PixelIndexable> Default for Bombs<T> {
+    fn default() -> Self {
+        Self::new(0.05, 6, 0.002, 0.2)
+    }
+}
+
+impl<T: PixelIndexable> Effect<T> for Bombs<T> {
+    fn tick(&mut self, color: &mut Color) {
+        if self.rng.gen_bool(self.density as f64) {
+            self.drop_bomb(*color);
+        }
+        let blast_color = Color::new(100.0, 80.0, color.hue).shift_hue(40.0);
+        for i in 0..NUM_BOMBS {
+            let bomb = &mut self.bombs[i];
+            if !bomb.is_alive() {
+                continue;
+            }
+            bomb.tick(self.gravity, 0.0);
+            if bomb.height < 0.0 {
+                let dir = bomb.dir;
+                bomb.kill();
+                self.explode(dir, blast_color);
+            }
+        }
+        for crawler in self.shrapnel.iter_mut().flatten() {
+            if crawler.step() {
+                // Cool as the blast spreads out
+                crawler.color.l *= 0.85;
+            }
+        }
+    }
+
+    fn render(&self, _color: Color, model: &mut T) {
+        model.map_pixels(|_idx, px| px.mix(&Color::new(0.0, 0.0, px.hue), self.fade));
+        for bomb in &self.bombs {
+            bomb.render(model);
+        }
+        for crawler in self.shrapnel.iter().flatten() {
+            if crawler.is_alive() {
+                *model.get_mut(crawler.idx) = crawler.color;
+            }
+        }
+    }
+
+    fn rotate_cw(&mut self, _color: &mut Color, out: &mut Readout) {
+        match self.control {
+            Control::Density => {
+                self.density = (self.density * 1.2).min(1.0);
+                out.show(format_args!("Density: {}", self.density));
+            }
+            Control::Radius => {
+                self.radius = (self.radius + 1).min(20);
+                out.show(format_args!("Radius: {}", self.radius));
+            }
+        }
+    }
+
+    fn rotate_ccw(&mut self, _color: &mut Color, out: &mut Readout) {
+        match self.control {
+            Control::Density => {
+                self.density = (self.density * 0.8).max(0.005);
+                out.show(format_args!("Density: {}", self.density));
+            }
+            Control::Radius => {
+                self.radius = self.radius.saturating_sub(1).max(1);
+                out.show(format_args!("Radius: {}", self.radius));
+            }
+        }
+    }
+
+    fn click(&mut self, _color: &mut Color, _out: &mut Readout) {
+        self.control = self.control.next();
+    }
+}
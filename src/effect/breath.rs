@@ -0,0 +1,207 @@
+use crate::{
+    effect::{Readout, TICKS_PER_SECOND},
+    Color, Effect, PixelIndexable,
+};
+use core::f32::consts::PI;
+use num_traits::Float;
+use palette::Hue;
+
+// Steepness of the passive exhale's exponential recoil
+const RECOIL: f32 = 4.0;
+
+#[derive(Clone, Copy, Debug)]
+pub enum Pattern {
+    /// Inhale for 40% and exhale for 60% of each breath at the configured rate
+    Paced,
+    /// Inhale 4s, hold 7s, exhale 8s
+    FourSevenEight,
+    /// Inhale, hold, exhale and hold for 4s each
+    Box,
+}
+
+impl Pattern {
+    pub fn next(self) -> Self {
+        use Pattern::*;
+        match self {
+            Paced => FourSevenEight,
+            FourSevenEight => Box,
+            Box => Paced,
+        }
+    }
+    pub fn prev(self) -> Self {
+        self.next().next()
+    }
+    /// Inhale, hold, exhale and hold durations in seconds
+    pub fn phases(self, bpm: f32) -> [f32; 4] {
+        use Pattern::*;
+        match self {
+            Paced => {
+                let period = 60.0 / bpm;
+                [period * 0.4, 0.0, period * 0.6, 0.0]
+            }
+            FourSevenEight => [4.0, 7.0, 8.0, 0.0],
+            Box => [4.0, 4.0, 4.0, 4.0],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Control {
+    Rate,
+    Pattern,
+    Drift,
+}
+
+impl Control {
+    pub fn next(self) -> Self {
+        // Spelled out, as a glob import would clash with the Pattern enum
+        match self {
+            Control::Rate => Control::Pattern,
+            Control::Pattern => Control::Drift,
+            Control::Drift => Control::Rate,
+        }
+    }
+}
+
+pub struct Breath {
+    pattern: Pattern,
+    bpm: f32,
+    // Hue drift in degrees per second, while drifting
+    drift: f32,
+    drifting: bool,
+    // Lightness at empty lungs, as a fraction of full
+    floor: f32,
+    // Ticks since the pattern started; counted as an integer so long sessions don't drift
+    ticks: u32,
+    control: Control,
+}
+
+impl Breath {
+    pub fn new(pattern: Pattern, bpm: f32, drift: f32, floor: f32) -> Self {
+        Self {
+            pattern,
+            bpm,
+            drift,
+            drifting: true,
+            floor,
+            ticks: 0,
+            control: Control::Rate,
+        }
+    }
+
+    /// Seconds into the current breath.  Phases needn't be whole ticks long, so this is worked
+    /// out from the total elapsed time rather than by counting ticks per phase.
+    fn elapsed(&self) -> f32 {
+        let cycle: f32 = self.pattern.phases(self.bpm).iter().sum();
+        // f64 keeps a tick's precision however long the lantern has been running
+        (self.ticks as f64 / TICKS_PER_SECOND as f64 % cycle as f64) as f32
+    }
+
+    /// Lung volume from 0.0 to 1.0 at the current point in the breath
+    fn level(&self) -> f32 {
+        let [inhale, hold_in, exhale, _] = self.pattern.phases(self.bpm);
+        let t = self.elapsed();
+        if t < inhale {
+            // Active inhale: flow is strongest at the start and tapers off as the lungs fill
+            let p = t / inhale;
+            (p * PI / 2.0).sin()
+        } else if t < inhale + hold_in {
+            1.0
+        } else if t < inhale + hold_in + exhale {
+            // Passive exhale: elastic recoil empties the lungs exponentially
+            let p = (t - inhale - hold_in) / exhale;
+            ((-RECOIL * p).exp() - (-RECOIL).exp()) / (1.0 - (-RECOIL).exp())
+        } else {
+            0.0
+        }
+    }
+
+    fn set_bpm(&mut self, bpm: f32, out: &mut Readout) {
+        self.bpm = bpm.clamp(2.0, 20.0);
+        self.pattern = Pattern::Paced;
+        self.ticks = 0;
+        out.show(format_args!("Breaths per minute: {}", self.bpm));
+    }
+
+    fn adjust(&mut self, up: bool, out: &mut Readout) {
+        match self.control {
+            Control::Rate => self.set_bpm(self.bpm + if up { 0.5 } else { -0.5 }, out),
+            Control::Pattern => {
+                self.pattern = if up {
+                    self.pattern.next()
+                } else {
+                    self.pattern.prev()
+                };
+                self.ticks = 0;
+                out.show(format_args!("Pattern: {:?}", self.pattern));
+            }
+            Control::Drift => {
+                self.drifting = !self.drifting;
+                out.show(format_args!(
+                    "Hue drift: {}",
+                    if self.drifting { "on" } else { "off" }
+                ));
+            }
+        }
+    }
+}
+
+impl Default for Breath {
+    fn default() -> Self {
+        Self::new(Pattern::Paced, 6.0, 1.0, 0.15)
+    }
+}
+
+impl<T: PixelIndexable> Effect<T> for Breath {
+    fn tick(&mut self, color: &mut Color) {
+        self.ticks = self.ticks.wrapping_add(1);
+        if self.drifting {
+            *color = color.shift_hue(self.drift / TICKS_PER_SECOND as f32);
+        }
+    }
+
+    fn render(&self, color: Color, model: &mut T) {
+        let level = self.floor + (1.0 - self.floor) * self.level();
+        model.set_all(Color {
+            l: color.l * level,
+            ..color
+        });
+    }
+
+    fn rotate_cw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.adjust(true, out);
+    }
+
+    fn rotate_ccw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.adjust(false, out);
+    }
+
+    fn click(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.control = self.control.next();
+        out.show(format_args!("Set: {:?}", self.control));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn odd_rates_keep_time() {
+        // 7 bpm breaths are 8.57s, which doesn't split into whole ticks
+        let mut breath = Breath::new(Pattern::Paced, 7.0, 0.0, 0.15);
+        breath.ticks = 60 * TICKS_PER_SECOND;
+        // Seven whole breaths in a minute, so the next one is just starting
+        assert!(breath.elapsed() < 1e-3 || breath.elapsed() > 60.0 / 7.0 - 1e-3);
+        assert!(breath.level() < 1e-3);
+    }
+
+    #[test]
+    fn box_breathing_holds_full() {
+        let mut breath = Breath::new(Pattern::Box, 6.0, 0.0, 0.15);
+        breath.ticks = 6 * TICKS_PER_SECOND;
+        assert_eq!(breath.level(), 1.0);
+        breath.ticks = 14 * TICKS_PER_SECOND;
+        assert_eq!(breath.level(), 0.0);
+    }
+}
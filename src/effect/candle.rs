@@ -0,0 +1,126 @@
+use crate::{blackbody, effect::Readout, Color, Effect, FaceType, PixelIndexable};
+use core::{f32::consts::PI, marker::PhantomData};
+use num_traits::Float;
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+// Number of octaves summed for the 1/f flicker
+const OCTAVES: usize = 5;
+const CORE_KELVIN: f32 = 1900.0;
+const EMBER_KELVIN: f32 = 1000.0;
+
+pub struct Candle<T: PixelIndexable> {
+    // Strength of the draft, 0.0 for still air
+    draft: f32,
+    // How deeply the flicker dims the flame
+    flicker_depth: f32,
+    gutter_chance: f32,
+    // Voss-McCartney pink noise state
+    octaves: [f32; OCTAVES],
+    counter: u32,
+    // Remaining depth of the current gutter
+    gutter: f32,
+    // Displacement and velocity of the flame core on the top face, in pixels
+    sway: (f32, f32),
+    sway_vel: (f32, f32),
+    rng: SmallRng,
+    _pd: PhantomData<T>,
+}
+
+impl<T: PixelIndexable> Candle<T> {
+    pub fn new(draft: f32, flicker_depth: f32, gutter_chance: f32) -> Self {
+        Self {
+            draft,
+            flicker_depth,
+            gutter_chance,
+            octaves: [0.5; OCTAVES],
+            counter: 0,
+            gutter: 0.0,
+            sway: (0.0, 0.0),
+            sway_vel: (0.0, 0.0),
+            rng: SmallRng::seed_from_u64(1234),
+            _pd: PhantomData,
+        }
+    }
+
+    /// 1/f noise: octave `n` is re-rolled every 2^n ticks, so slow wavers and quick
+    /// flutters are both present with falling amplitude.
+    fn step_flicker(&mut self) {
+        self.counter = self.counter.wrapping_add(1);
+        let octave = (self.counter.trailing_zeros() as usize).min(OCTAVES - 1);
+        self.octaves[octave] = self.rng.gen_range(0.0, 1.0);
+    }
+
+    fn brightness(&self) -> f32 {
+        let flicker = self.octaves.iter().sum::<f32>() / OCTAVES as f32;
+        let depth = self.flicker_depth * (1.0 + self.draft);
+        (1.0 - depth * flicker) * (1.0 - 0.7 * self.gutter)
+    }
+}
+
+impl<T: PixelIndexable> Default for Candle<T> {
+    fn default() -> Self {
+        Self::new(0.2, 0.3, 0.003)
+    }
+}
+
+impl<T: PixelIndexable> Effect<T> for Candle<T> {
+    fn tick(&mut self, _color: &mut Color) {
+        self.step_flicker();
+        if self.gutter > 0.01 {
+            self.gutter *= 0.9;
+        } else if self.rng.gen_bool(self.gutter_chance as f64) {
+            self.gutter = self.rng.gen_range(0.5, 1.0);
+        }
+        // Damped spring pulling the flame upright, kicked about by the draft
+        let (x, y) = self.sway;
+        let (mut vx, mut vy) = self.sway_vel;
+        vx += -0.2 * x - 0.3 * vx;
+        vy += -0.2 * y - 0.3 * vy;
+        if self.draft > 0.0 && self.rng.gen_bool(0.2) {
+            vx += self.rng.gen_range(-self.draft, self.draft);
+            vy += self.rng.gen_range(-self.draft, self.draft);
+        }
+        self.sway_vel = (vx, vy);
+        self.sway = (x + vx, y + vy);
+    }
+
+    fn render(&self, color: Color, model: &mut T) {
+        let brightness = self.brightness();
+        let (sx, sy) = self.sway;
+        for idx in model.iter_pixels() {
+            let (dir, height) = idx.as_spherical();
+            let (sin, cos) = (dir * 2.0 * PI).sin_cos();
+            let (level, kelvin) = match idx.face_type() {
+                FaceType::Top => {
+                    // Distance from the flame core, in pixels
+                    let r = (1.0 - height) * 7.0;
+                    let (dx, dy) = (r * cos - sx, r * sin - sy);
+                    let core = (-(dx * dx + dy * dy) / 1.5).exp();
+                    (core, EMBER_KELVIN + (CORE_KELVIN - EMBER_KELVIN) * core)
+                }
+                FaceType::Side => {
+                    // Light falls off down the sides, brighter on the side the flame leans to
+                    let lean = (1.0 + 0.3 * (sx * cos + sy * sin)).max(0.0);
+                    let fall = height / (4.0 / 7.0);
+                    (0.3 * fall * fall * lean, EMBER_KELVIN + 400.0 * fall)
+                }
+            };
+            let base = blackbody(kelvin);
+            // The glow follows the global Luma knob like every other effect
+            *model.get_mut(idx) = Color {
+                l: base.l * (level * brightness).min(1.0) * color.l / 100.0,
+                ..base
+            };
+        }
+    }
+
+    fn rotate_cw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.draft = (self.draft + 0.05).min(1.0);
+        out.show(format_args!("Draft: {}", self.draft));
+    }
+
+    fn rotate_ccw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.draft = (self.draft - 0.05).max(0.0);
+        out.show(format_args!("Draft: {}", self.draft));
+    }
+}
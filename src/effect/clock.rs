@@ -0,0 +1,121 @@
+use crate::{
+    effect::{Readout, TICKS_PER_SECOND},
+    Color, Effect, FaceType, PixelIndexable, TimeOfDay,
+};
+use core::marker::PhantomData;
+use palette::Hue;
+
+#[derive(Clone, Copy, Debug)]
+pub enum Control {
+    Locked,
+    Hours,
+    Minutes,
+}
+
+impl Control {
+    pub fn next(self) -> Self {
+        use Control::*;
+        match self {
+            Locked => Hours,
+            Hours => Minutes,
+            Minutes => Locked,
+        }
+    }
+}
+
+/// Shows the time of day: the hour as the hue of the sides, minutes as how far up the sides
+/// the band reaches, and seconds as a dot circling the top face.
+pub struct Clock<T: PixelIndexable> {
+    time: TimeOfDay,
+    // Ticks since the last whole second, for when no time source is syncing us
+    subticks: u32,
+    // Set when the time has been changed from the knobs and should be written back
+    adjusted: bool,
+    control: Control,
+    _pd: PhantomData<T>,
+}
+
+impl<T: PixelIndexable> Clock<T> {
+    pub fn new(time: TimeOfDay) -> Self {
+        Self {
+            time,
+            subticks: 0,
+            adjusted: false,
+            control: Control::Locked,
+            _pd: PhantomData,
+        }
+    }
+
+    fn adjust(&mut self, dir: i32, out: &mut Readout) {
+        let delta = match self.control {
+            Control::Locked => return,
+            Control::Hours => 3600,
+            Control::Minutes => 60,
+        };
+        self.time = self.time.add_seconds(dir * delta);
+        self.adjusted = true;
+        out.show(format_args!(
+            "Time: {:02}:{:02}",
+            self.time.hours, self.time.minutes
+        ));
+    }
+}
+
+impl<T: PixelIndexable> Default for Clock<T> {
+    fn default() -> Self {
+        Self::new(TimeOfDay::default())
+    }
+}
+
+impl<T: PixelIndexable> Effect<T> for Clock<T> {
+    fn tick(&mut self, _color: &mut Color) {
+        self.subticks += 1;
+        if self.subticks >= TICKS_PER_SECOND {
+            self.subticks = 0;
+            self.time = self.time.add_seconds(1);
+        }
+    }
+
+    fn render(&self, color: Color, model: &mut T) {
+        let hour = self.time.hours % 12;
+        let band = color.shift_hue(hour as f32 * 30.0);
+        let minutes = self.time.minutes as f32 / 60.0;
+        let seconds = self.time.seconds as f32 / 60.0;
+        let dim = Color {
+            l: band.l * 0.1,
+            ..band
+        };
+        for idx in model.iter_pixels() {
+            let (_, height) = idx.as_spherical();
+            *model.get_mut(idx) = match idx.face_type() {
+                // Side heights run from 0.0 at the bottom row to 4/7 at the top row
+                FaceType::Side if height <= minutes * 4.0 / 7.0 => band,
+                _ => dim,
+            };
+        }
+        *model.get_spherical_mut(seconds, 5.0 / 7.0) = Color::new(100.0, 0.0, band.hue);
+    }
+
+    fn rotate_cw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.adjust(1, out);
+    }
+
+    fn rotate_ccw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.adjust(-1, out);
+    }
+
+    fn click(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.control = self.control.next();
+        out.show(format_args!("Set: {:?}", self.control));
+    }
+
+    fn sync_time(&mut self, now: TimeOfDay) -> Option<TimeOfDay> {
+        if self.adjusted {
+            self.adjusted = false;
+            Some(self.time)
+        } else {
+            self.time = now;
+            None
+        }
+    }
+}
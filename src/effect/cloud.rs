@@ -1,45 +1,160 @@
-use crate::{math::noise::Simplex, Color, Effect, PixelIndexable};
+use crate::{
+    effect::{Readout, TICKS_PER_SECOND},
+    math::{noise::Simplex, smoothstep, Vec3},
+    Color, Effect, PixelIndexable,
+};
 use core::marker::PhantomData;
-//use num_traits::float::FloatCore;
-//use rand::rngs::SmallRng;
-//use rand::{Rng, SeedableRng};
-//use rtt_target::rprintln;
+use num_traits::Float;
+use palette::Mix;
+
+#[derive(Clone, Copy, Debug)]
+pub enum Control {
+    Speed,
+    Octaves,
+    Persistence,
+    Scale,
+    Billow,
+}
+
+impl Control {
+    pub fn next(self) -> Self {
+        use Control::*;
+        match self {
+            Speed => Octaves,
+            Octaves => Persistence,
+            Persistence => Scale,
+            Scale => Billow,
+            Billow => Speed,
+        }
+    }
+}
+
 pub struct Cloud<T: PixelIndexable> {
+    // Drift of the clouds, in noise units per second
     speed: f32,
     offset: f32,
+    octaves: usize,
+    // Amplitude of each octave relative to the one before
+    persistence: f32,
+    // Frequency of the first octave
+    scale: f32,
+    // Puffy billow noise rather than smooth fractal noise
+    billow: bool,
+    // Color showing through gaps in the cloud, or black if none
+    sky: Option<Color>,
+    control: Control,
     noise: Simplex,
     _pd: PhantomData<T>,
 }
 
 impl<T: PixelIndexable> Cloud<T> {
-    pub fn default() -> Self {
-        let speed = 10f32;
-        let noise = Simplex::new(137);
-        let offset = 0.0.into();
-        let _pd = PhantomData;
+    pub fn new(
+        speed: f32,
+        octaves: usize,
+        persistence: f32,
+        scale: f32,
+        sky: Option<Color>,
+    ) -> Self {
         Self {
             speed,
-            offset,
-            noise,
-            _pd,
+            offset: 0.0,
+            octaves,
+            persistence,
+            scale,
+            billow: false,
+            sky,
+            control: Control::Speed,
+            noise: Simplex::new(137),
+            _pd: PhantomData,
+        }
+    }
+
+    /// Cloud cover at a point, from 0.0 to 1.0
+    fn density(&self, pos: Vec3) -> f32 {
+        let val = if self.billow {
+            let Vec3 { x, y, z } = pos * self.scale;
+            // billow_3d sums the octaves without normalising them
+            let max_amp: f32 = (0..self.octaves)
+                .map(|n| self.persistence.powi(n as i32))
+                .sum();
+            self.noise
+                .billow_3d(self.octaves, x, y, z, self.persistence, 2.0)
+                / max_amp
+        } else {
+            let Vec3 { x, y, z } = pos;
+            self.noise
+                .sum_octave_3d(self.octaves as isize, x, y, z, self.persistence, self.scale)
+        };
+        (val + 1.0) / 2.0
+    }
+
+    fn adjust(&mut self, up: bool, out: &mut Readout) {
+        let factor = if up { 1.2 } else { 1.0 / 1.2 };
+        match self.control {
+            Control::Speed => {
+                self.speed = (self.speed * factor).clamp(0.01, 2.0);
+                out.show(format_args!("Speed: {}", self.speed));
+            }
+            Control::Octaves => {
+                self.octaves = if up {
+                    (self.octaves + 1).min(6)
+                } else {
+                    (self.octaves - 1).max(1)
+                };
+                out.show(format_args!("Octaves: {}", self.octaves));
+            }
+            Control::Persistence => {
+                self.persistence = (self.persistence * factor).clamp(0.1, 1.0);
+                out.show(format_args!("Persistence: {}", self.persistence));
+            }
+            Control::Scale => {
+                self.scale = (self.scale * factor).clamp(0.2, 8.0);
+                out.show(format_args!("Scale: {}", self.scale));
+            }
+            Control::Billow => {
+                self.billow = !self.billow;
+                out.show(format_args!("Billow: {}", self.billow));
+            }
         }
     }
 }
 
+impl<T: PixelIndexable> Default for Cloud<T> {
+    fn default() -> Self {
+        Self::new(0.1, 3, 0.5, 1.5, Some(Color::new(40.0, 50.0, 260.0)))
+    }
+}
+
 impl<T: PixelIndexable> Effect<T> for Cloud<T> {
     fn tick(&mut self, _color: &mut Color) {
-        self.offset += self.speed;
+        self.offset += self.speed / TICKS_PER_SECOND as f32;
     }
     fn render(&self, color: Color, model: &mut T) {
+        // Sampling on the sphere keeps the noise seamless around the sides, and sliding it along
+        // one axis blows the clouds across the lantern
+        let drift = Vec3::new(self.offset, 0.0, 0.0);
         for idx in model.iter_pixels() {
             let (dir, height) = idx.as_spherical();
-            let x = dir * 256.0;
-            let y = height * 256.0;
-            let z = self.offset;
-            let val = self.noise.noise_3d(x, y, z);
-            //let val = self.noise.billow_3d(4, x, y, z, 0.5, 0.5);
-            let l = (val + 1.0) * 50.0;
-            *model.get_mut(idx) = Color { l, ..color };
+            let density = self.density(Vec3::from_spherical(dir, height) + drift);
+            // Noise sits mostly around the middle, so stretch it to open up gaps of clear sky
+            let t = smoothstep(0.35, 0.75, density);
+            *model.get_mut(idx) = match self.sky {
+                Some(sky) => sky.mix(&color, t),
+                None => Color {
+                    l: color.l * t,
+                    ..color
+                },
+            };
         }
     }
+    fn rotate_cw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.adjust(true, out);
+    }
+    fn rotate_ccw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.adjust(false, out);
+    }
+    fn click(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.control = self.control.next();
+        out.show(format_args!("Set: {:?}", self.control));
+    }
 }
@@ -0,0 +1,176 @@
+use crate::{
+    effect::{Readout, TICKS_PER_SECOND},
+    math::smoothstep,
+    pixelindex::PixelIterator,
+    Color, Effect, PixelIndexable, TimeOfDay,
+};
+use core::{f32::consts::PI, marker::PhantomData};
+use generic_array::{ArrayLength, GenericArray};
+use num_traits::Float;
+use palette::Mix;
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+const SECONDS_PER_DAY: f32 = 86400.0;
+
+#[derive(Clone, Copy)]
+pub enum Mode {
+    /// Run through a whole day every `day_length` seconds
+    Compressed,
+    /// Follow the wall clock, falling back to `Compressed` until the time is known
+    WallClock,
+}
+
+impl Mode {
+    pub fn next(self) -> Self {
+        use Mode::*;
+        match self {
+            Compressed => WallClock,
+            WallClock => Compressed,
+        }
+    }
+}
+
+pub struct DayNight<T: PixelIndexable>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    // Length of a compressed day, in seconds
+    day_length: f32,
+    // Time of day from 0.0 (midnight) through 0.5 (noon) to 1.0
+    time: f32,
+    mode: Mode,
+    // Seconds since midnight from the wall clock, if one has been provided
+    clock: Option<f32>,
+    // Twinkle phase of the star at each pixel, negative where there is no star
+    stars: GenericArray<f32, T::SIZE>,
+    twinkle: f32,
+    _pd: PhantomData<T>,
+}
+
+impl<T: PixelIndexable> DayNight<T>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    pub fn new(day_length: f32, mode: Mode) -> Self {
+        let mut rng = SmallRng::seed_from_u64(1234);
+        let mut stars: GenericArray<f32, T::SIZE> = GenericArray::default();
+        for star in stars.iter_mut() {
+            *star = if rng.gen_ratio(1, 4) {
+                rng.gen_range(0.0, 2.0 * PI)
+            } else {
+                -1.0
+            };
+        }
+        Self {
+            day_length,
+            time: 0.25,
+            mode,
+            clock: None,
+            stars,
+            twinkle: 0.0,
+            _pd: PhantomData,
+        }
+    }
+
+    /// Provide the wall clock time, used in `Mode::WallClock`.
+    pub fn set_clock(&mut self, seconds_since_midnight: u32) {
+        self.clock = Some(seconds_since_midnight as f32);
+    }
+
+    /// Elevation of the sun from -1.0 (midnight) to 1.0 (noon)
+    fn sun_elevation(&self) -> f32 {
+        -(self.time * 2.0 * PI).cos()
+    }
+
+    fn sky(&self, dir: f32, height: f32) -> Color {
+        let elevation = self.sun_elevation();
+        let night = Color::new(2.0, 10.0, 270.0);
+        let day = Color::new(55.0 + 15.0 * (1.0 - height), 45.0, 245.0);
+        let dusk = Color::new(45.0, 70.0, 45.0);
+        let daylight = smoothstep(-0.15, 0.3, elevation);
+        let sky = night.mix(&day, daylight);
+        // Sunrise and sunset glow, strongest low in the sky towards the sun
+        let twilight = (1.0 - elevation.abs() / 0.3).max(0.0);
+        let towards_sun = (angle_between(dir, height, self.time, 0.0) + 1.0) / 2.0;
+        sky.mix(&dusk, twilight * towards_sun * (1.0 - height))
+    }
+}
+
+impl<T: PixelIndexable> Default for DayNight<T>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    fn default() -> Self {
+        Self::new(300.0, Mode::Compressed)
+    }
+}
+
+impl<T: PixelIndexable> Effect<T> for DayNight<T>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    fn tick(&mut self, _color: &mut Color) {
+        let dt = 1.0 / TICKS_PER_SECOND as f32;
+        self.twinkle += dt;
+        if let Some(clock) = self.clock.as_mut() {
+            *clock = (*clock + dt) % SECONDS_PER_DAY;
+        }
+        match (self.mode, self.clock) {
+            (Mode::WallClock, Some(clock)) => self.time = clock / SECONDS_PER_DAY,
+            _ => self.time = (self.time + dt / self.day_length) % 1.0,
+        }
+    }
+
+    fn render(&self, _color: Color, model: &mut T) {
+        let elevation = self.sun_elevation();
+        let sun = Color::new(100.0, 40.0, 80.0);
+        let moon = Color::new(70.0, 5.0, 250.0);
+        let px_iter: PixelIterator<T> = PixelIterator::all();
+        for idx in px_iter {
+            let (dir, height) = idx.as_spherical();
+            let mut px = self.sky(dir, height);
+            let star = self.stars[idx.usize()];
+            let darkness = 1.0 - smoothstep(-0.3, 0.0, elevation);
+            if star >= 0.0 && darkness > 0.0 {
+                let twinkle = 0.5 + 0.5 * (star + self.twinkle * (2.0 + star)).sin();
+                px.l += 30.0 * twinkle * darkness;
+            }
+            if elevation > -0.1 {
+                let glow = angle_between(dir, height, self.time, elevation);
+                px = px.mix(&sun, ((glow - 0.8) * 5.0).max(0.0));
+            }
+            if elevation < 0.1 {
+                let glow = angle_between(dir, height, self.time + 0.5, -elevation);
+                px = px.mix(&moon, ((glow - 0.9) * 8.0).max(0.0));
+            }
+            *model.get_mut(idx) = px;
+        }
+    }
+
+    fn rotate_cw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.day_length = (self.day_length * 1.25).min(SECONDS_PER_DAY);
+        out.show(format_args!("Day length: {}s", self.day_length));
+    }
+
+    fn rotate_ccw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.day_length = (self.day_length * 0.8).max(30.0);
+        out.show(format_args!("Day length: {}s", self.day_length));
+    }
+
+    fn click(&mut self, _color: &mut Color, _out: &mut Readout) {
+        self.mode = self.mode.next();
+    }
+
+    fn sync_time(&mut self, now: TimeOfDay) -> Option<TimeOfDay> {
+        self.set_clock(now.seconds_since_midnight());
+        None
+    }
+}
+
+/// Cosine of the angle between two points given as spherical (dir, height), treating height as
+/// elevation from the horizon (0.0) to the zenith (1.0).
+fn angle_between(dir_a: f32, height_a: f32, dir_b: f32, height_b: f32) -> f32 {
+    let (ea, eb) = (height_a * PI / 2.0, height_b * PI / 2.0);
+    let da = (dir_a - dir_b) * 2.0 * PI;
+    ea.sin() * eb.sin() + ea.cos() * eb.cos() * da.cos()
+}
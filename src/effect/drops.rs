@@ -1,43 +1,115 @@
-use crate::{Color, Lantern};
-use rand::rngs::SmallRng;
-use rand::{Rng, SeedableRng};
+use crate::{
+    effect::{particle::Particle, Readout},
+    Color, Effect, PixelIndexable,
+};
+use core::marker::PhantomData;
+use palette::{Hue, Mix};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
 
 const NUM_DROPS: usize = 16;
-// TODO const generics
-pub struct Drops {
-    drops: [(Color, u8, u8); NUM_DROPS],
+const NUM_SPLASHES: usize = 24;
+const SPLASH_SIZE: usize = 3;
+const SPLASH_GRAVITY: f32 = 0.01;
+
+pub struct Drops<T: PixelIndexable> {
+    drops: [Particle; NUM_DROPS],
+    splashes: [Particle; NUM_SPLASHES],
+    // Chance per tick of a new drop appearing
+    spawn_chance: f32,
+    // Random hue variation of each drop around the base color, in degrees
+    hue_spread: f32,
+    // Fraction of the way the trail fades to black each tick
+    fade: f32,
     rng: SmallRng,
+    _pd: PhantomData<T>,
 }
 
-impl Drops {
-    pub fn new(color: Color) -> Self {
-        let mut rng = SmallRng::seed_from_u64(1234);
-        let mut drops = [(color, 0, 7); NUM_DROPS];
-        for drop in drops.iter_mut() {
-            //drop.0.h = rng.gen_range(0, HUE_MAX as u16);
-            //drop.0.s = 0;
-            drop.1 = rng.gen_range(0, 20);
-        }
-        Self { drops, rng }
-    }
-    pub fn tick(&mut self, model: &mut Lantern) {
-        model.darken(0.1);
-        model.saturate(0.2);
-        //model.shift_hue_all(50);
-        for (color, angle, height) in self.drops.iter_mut() {
-            if self.rng.gen_ratio(1, 10) {
-                if *height == 0 {
-                    *height = 7;
-                    *angle = self.rng.gen_range(0, 20);
-                //color.h = self.rng.gen_range(0, HUE_MAX as u16);
-                //color.s = 0;
-                //color.s = self.rng.gen_range(0, 255);
-                } else {
-                    *height -= 1;
+impl<T: PixelIndexable> Drops<T> {
+    pub fn new(spawn_chance: f32, hue_spread: f32, fade: f32) -> Self {
+        Self {
+            drops: [Particle::dead(); NUM_DROPS],
+            splashes: [Particle::dead(); NUM_SPLASHES],
+            spawn_chance,
+            hue_spread,
+            fade,
+            rng: SmallRng::seed_from_u64(1234),
+            _pd: PhantomData,
+        }
+    }
+
+    fn spawn(&mut self, color: Color) {
+        let rng = &mut self.rng;
+        if let Some(p) = self.drops.iter_mut().find(|p| !p.is_alive()) {
+            let hue = rng.gen_range(-1.0, 1.0) * self.hue_spread;
+            // Drops form anywhere on the top face and run over the edge and down the sides
+            *p = Particle::new(
+                rng.gen_range(0.0, 1.0),
+                rng.gen_range(5.0 / 8.0, 1.0),
+                0.0,
+                -rng.gen_range(0.02, 0.06),
+                color.shift_hue(hue),
+            );
+        }
+    }
+
+    fn splash(&mut self, dir: f32, color: Color) {
+        let rng = &mut self.rng;
+        let free = self.splashes.iter_mut().filter(|p| !p.is_alive());
+        for p in free.take(SPLASH_SIZE) {
+            let ddir = rng.gen_range(-0.015, 0.015);
+            let dheight = rng.gen_range(0.03, 0.06);
+            *p = Particle::new(dir, 0.0, ddir, dheight, color);
+        }
+    }
+}
+
+impl<T: PixelIndexable> Default for Drops<T> {
+    fn default() -> Self {
+        Self::new(0.3, 60.0, 0.2)
+    }
+}
+
+impl<T: PixelIndexable> Effect<T> for Drops<T> {
+    fn tick(&mut self, color: &mut Color) {
+        if self.rng.gen_bool(self.spawn_chance as f64) {
+            self.spawn(*color);
+        }
+        let mut landed = [None; NUM_DROPS];
+        for (p, landed) in self.drops.iter_mut().zip(landed.iter_mut()) {
+            if p.is_alive() {
+                p.tick(0.0, 0.0);
+                if p.height < 0.0 {
+                    *landed = Some((p.dir, p.color));
+                    p.kill();
                 }
             }
-            let px = model.get_cylinder_pixel(*angle, *height);
-            *px = *color;
         }
+        for &(dir, color) in landed.iter().flatten() {
+            self.splash(dir, color);
+        }
+        for p in self.splashes.iter_mut().filter(|p| p.is_alive()) {
+            p.tick(SPLASH_GRAVITY, 0.0);
+            if p.height < 0.0 {
+                p.kill();
+            }
+        }
+    }
+
+    fn render(&self, _color: Color, model: &mut T) {
+        // Drops leave a fading trail of the previous frames behind them
+        model.map_pixels(|_idx, px| px.mix(&Color::new(0.0, 0.0, px.hue), self.fade));
+        for p in self.drops.iter().chain(self.splashes.iter()) {
+            p.render(model);
+        }
+    }
+
+    fn rotate_cw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.hue_spread = (self.hue_spread + 10.0).min(180.0);
+        out.show(format_args!("Hue spread: {}", self.hue_spread));
+    }
+
+    fn rotate_ccw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.hue_spread = (self.hue_spread - 10.0).max(0.0);
+        out.show(format_args!("Hue spread: {}", self.hue_spread));
     }
 }
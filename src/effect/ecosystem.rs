@@ -0,0 +1,263 @@
+use crate::{
+    effect::{particle::Heading, Readout},
+    pixelindex::PixelIterator,
+    Color, Effect, PixelIndex, PixelIndexable,
+};
+use core::marker::PhantomData;
+use generic_array::{ArrayLength, GenericArray};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+use typenum::Unsigned;
+
+const NEIGHBOURS: [Heading; 4] = [Heading::Up, Heading::Down, Heading::Left, Heading::Right];
+// Ticks a species may stay extinct before it is reseeded
+const EXTINCTION_GRACE: u32 = 50;
+
+/// Metabolism and breeding parameters for an animal species
+#[derive(Clone, Copy)]
+struct Species {
+    hue: f32,
+    // Energy burnt per tick
+    metabolism: f32,
+    // Fraction of food energy gained when eating
+    efficiency: f32,
+    // Energy above which the animal splits in two
+    breed: f32,
+    // Population added when the species has died out
+    reseed: usize,
+}
+
+const PREY: Species = Species {
+    hue: 80.0,
+    metabolism: 0.02,
+    efficiency: 0.5,
+    breed: 1.0,
+    reseed: 8,
+};
+
+const PREDATOR: Species = Species {
+    hue: 20.0,
+    metabolism: 0.015,
+    efficiency: 0.6,
+    breed: 1.5,
+    reseed: 3,
+};
+
+const PLANT_HUE: f32 = 140.0;
+
+pub struct Ecosystem<T: PixelIndexable>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    // Plant biomass in each cell, from 0.0 to 1.0
+    plants: GenericArray<f32, T::SIZE>,
+    // Energy of the herbivore in each cell, 0.0 where there is none
+    prey: GenericArray<f32, T::SIZE>,
+    // Energy of the predator in each cell, 0.0 where there is none
+    predators: GenericArray<f32, T::SIZE>,
+    growth: f32,
+    bite: f32,
+    // Ticks each species has been extinct
+    prey_gone: u32,
+    predators_gone: u32,
+    rng: SmallRng,
+    _pd: PhantomData<T>,
+}
+
+impl<T: PixelIndexable> Ecosystem<T>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    pub fn new(growth: f32, bite: f32) -> Self {
+        let mut rv = Self {
+            plants: GenericArray::default(),
+            prey: GenericArray::default(),
+            predators: GenericArray::default(),
+            growth,
+            bite,
+            prey_gone: 0,
+            predators_gone: 0,
+            rng: SmallRng::seed_from_u64(1234),
+            _pd: PhantomData,
+        };
+        rv.reseed();
+        rv
+    }
+
+    fn reseed(&mut self) {
+        for plant in self.plants.iter_mut() {
+            *plant = self.rng.gen_range(0.0, 1.0);
+        }
+        for (prey, predator) in self.prey.iter_mut().zip(self.predators.iter_mut()) {
+            *prey = 0.0;
+            *predator = 0.0;
+        }
+        self.scatter(PREY, false);
+        self.scatter(PREDATOR, true);
+    }
+
+    fn random_index(&mut self) -> PixelIndex<T> {
+        self.rng.gen_range(0, T::SIZE::to_usize()).into()
+    }
+
+    /// Drop a handful of animals into empty cells
+    fn scatter(&mut self, species: Species, predator: bool) {
+        for _ in 0..species.reseed {
+            let idx = self.random_index();
+            if self.is_empty(idx) {
+                let cells = if predator {
+                    &mut self.predators
+                } else {
+                    &mut self.prey
+                };
+                cells[idx.usize()] = species.breed / 2.0;
+            }
+        }
+    }
+
+    fn is_empty(&self, idx: PixelIndex<T>) -> bool {
+        self.prey[idx.usize()] <= 0.0 && self.predators[idx.usize()] <= 0.0
+    }
+
+    fn random_neighbour(&mut self, idx: PixelIndex<T>) -> Option<PixelIndex<T>> {
+        NEIGHBOURS[self.rng.gen_range(0, NEIGHBOURS.len())].step(idx)
+    }
+
+    fn empty_neighbour(&mut self, idx: PixelIndex<T>) -> Option<PixelIndex<T>> {
+        self.random_neighbour(idx).filter(|&n| self.is_empty(n))
+    }
+
+    fn grow(&mut self) {
+        for plant in self.plants.iter_mut() {
+            // Logistic regrowth, with a trickle of seeds so bare ground recovers
+            *plant = (*plant + self.growth * (*plant + 0.05) * (1.0 - *plant)).min(1.0);
+        }
+    }
+
+    fn graze(&mut self, idx: PixelIndex<T>) {
+        let i = idx.usize();
+        let mut energy = self.prey[i] - PREY.metabolism;
+        let eaten = self.plants[i].min(self.bite);
+        self.plants[i] -= eaten;
+        energy += eaten * PREY.efficiency;
+        self.prey[i] = 0.0;
+        if energy <= 0.0 {
+            return;
+        }
+        let dest = self.empty_neighbour(idx).unwrap_or(idx);
+        if energy > PREY.breed && dest != idx {
+            self.prey[i] = energy / 2.0;
+            self.prey[dest.usize()] = energy / 2.0;
+        } else {
+            self.prey[dest.usize()] = energy;
+        }
+    }
+
+    fn hunt(&mut self, idx: PixelIndex<T>) {
+        let i = idx.usize();
+        let mut energy = self.predators[i] - PREDATOR.metabolism;
+        self.predators[i] = 0.0;
+        if energy <= 0.0 {
+            return;
+        }
+        let mut dest = idx;
+        for &heading in NEIGHBOURS.iter() {
+            if let Some(n) = heading.step(idx) {
+                if self.prey[n.usize()] > 0.0 {
+                    energy += self.prey[n.usize()] * PREDATOR.efficiency;
+                    self.prey[n.usize()] = 0.0;
+                    dest = n;
+                    break;
+                }
+            }
+        }
+        if dest == idx {
+            dest = self.empty_neighbour(idx).unwrap_or(idx);
+        }
+        if energy > PREDATOR.breed && dest != idx {
+            self.predators[i] = energy / 2.0;
+            self.predators[dest.usize()] = energy / 2.0;
+        } else {
+            self.predators[dest.usize()] = energy;
+        }
+    }
+
+    fn census(&mut self) {
+        let count = |cells: &GenericArray<f32, T::SIZE>| cells.iter().filter(|&&e| e > 0.0).count();
+        let prey = count(&self.prey);
+        let predators = count(&self.predators);
+        self.prey_gone = if prey == 0 { self.prey_gone + 1 } else { 0 };
+        self.predators_gone = if predators == 0 {
+            self.predators_gone + 1
+        } else {
+            0
+        };
+        if self.prey_gone > EXTINCTION_GRACE {
+            self.scatter(PREY, false);
+            self.prey_gone = 0;
+        }
+        if self.predators_gone > EXTINCTION_GRACE {
+            self.scatter(PREDATOR, true);
+            self.predators_gone = 0;
+        }
+    }
+}
+
+impl<T: PixelIndexable> Default for Ecosystem<T>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    fn default() -> Self {
+        Self::new(0.02, 0.2)
+    }
+}
+
+impl<T: PixelIndexable> Effect<T> for Ecosystem<T>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    fn tick(&mut self, _color: &mut Color) {
+        self.grow();
+        // Only animals present at the start of the tick act, so nothing moves twice
+        let prey = self.prey.clone();
+        let predators = self.predators.clone();
+        let px_iter: PixelIterator<T> = PixelIterator::all();
+        for idx in px_iter {
+            let i = idx.usize();
+            if prey[i] > 0.0 && self.prey[i] == prey[i] {
+                self.graze(idx);
+            } else if predators[i] > 0.0 && self.predators[i] == predators[i] {
+                self.hunt(idx);
+            }
+        }
+        self.census();
+    }
+
+    fn render(&self, color: Color, model: &mut T) {
+        for idx in model.iter_pixels() {
+            let i = idx.usize();
+            let (hue, energy) = if self.predators[i] > 0.0 {
+                (PREDATOR.hue, self.predators[i] / PREDATOR.breed)
+            } else if self.prey[i] > 0.0 {
+                (PREY.hue, self.prey[i] / PREY.breed)
+            } else {
+                (PLANT_HUE, self.plants[i] * 0.3)
+            };
+            let l = color.l * (0.2 + 1.5 * energy.min(1.0));
+            *model.get_mut(idx) = Color::new(l, color.chroma, hue);
+        }
+    }
+
+    fn rotate_cw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.growth = (self.growth * 1.2).min(0.5);
+        out.show(format_args!("Growth: {}", self.growth));
+    }
+
+    fn rotate_ccw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.growth = (self.growth / 1.2).max(0.001);
+        out.show(format_args!("Growth: {}", self.growth));
+    }
+
+    fn click(&mut self, _color: &mut Color, _out: &mut Readout) {
+        self.reseed();
+    }
+}
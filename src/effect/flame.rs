@@ -1,4 +1,7 @@
-use crate::{pixelindex::PixelIterator, Color, Effect, PixelIndex, PixelIndexable};
+use crate::{
+    blackbody, effect::Readout, pixelindex::PixelIterator, Color, Effect, PixelIndex,
+    PixelIndexable,
+};
 use core::marker::PhantomData;
 use generic_array::{ArrayLength, GenericArray};
 use num_traits::Float;
@@ -6,6 +9,62 @@ use palette::Hue;
 use rand::{rngs::SmallRng, Rng, SeedableRng};
 use rtt_target::{rprint, rprintln};
 
+/// Mapping from heat to colour
+#[derive(Clone, Copy, Debug)]
+pub enum Palette {
+    /// Blackbody ramp from dull red embers to a yellow-white core
+    Fire,
+    /// Blue gas flame, paling towards the hottest point
+    Gas,
+    /// Green copper-salt flame
+    Chemical,
+    /// The current colour, shifting hue as it cools
+    Custom,
+}
+
+impl Palette {
+    pub fn next(self) -> Self {
+        use Palette::*;
+        match self {
+            Fire => Gas,
+            Gas => Chemical,
+            Chemical => Custom,
+            Custom => Fire,
+        }
+    }
+    pub fn prev(self) -> Self {
+        use Palette::*;
+        match self {
+            Fire => Custom,
+            Gas => Fire,
+            Chemical => Gas,
+            Custom => Chemical,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Control {
+    Palette,
+    Fuel,
+    Cool,
+    Flow,
+    Hue,
+}
+
+impl Control {
+    pub fn next(self) -> Self {
+        use Control::*;
+        match self {
+            Palette => Fuel,
+            Fuel => Cool,
+            Cool => Flow,
+            Flow => Hue,
+            Hue => Palette,
+        }
+    }
+}
+
 pub struct Flame<T: PixelIndexable>
 where
     T::SIZE: ArrayLength<f32>,
@@ -22,6 +81,8 @@ where
     heat_min: f32,
     heat_max: f32,
     hue_shift: f32,
+    palette: Palette,
+    control: Control,
     rng: SmallRng,
     _phantom: PhantomData<T>,
 }
@@ -50,9 +111,11 @@ where
             cool_min: 0.02,
             fuel_min: 0.95,
             fuel_max: 2.0,
-            heat_min: 0.0,
+            heat_min: 0.02,
             heat_max: 2.5,
             hue_shift: 70.0,
+            palette: Palette::Fire,
+            control: Control::Palette,
             rng,
             _phantom: PhantomData,
         }
@@ -106,6 +169,59 @@ where
             self.cells[idx.usize()] = 0.0;
         }
     }
+    fn heat_color(&self, val: f32, color: Color) -> Color {
+        // Lightness falls away faster than the heat so the cooling edges fade to black
+        let l = color.l * val.powf(1.5);
+        match self.palette {
+            Palette::Fire => Color {
+                l,
+                ..blackbody(1000.0 + 5000.0 * val)
+            },
+            Palette::Gas => Color::new(l, 80.0 * (1.0 - 0.7 * val), 270.0 - 60.0 * val),
+            Palette::Chemical => Color::new(l, 80.0 * (1.0 - 0.5 * val), 150.0 - 40.0 * val),
+            Palette::Custom => Color {
+                l,
+                ..color.shift_hue(self.hue_shift * (1.0 - val))
+            },
+        }
+    }
+    /// Scale a pair of min/max parameters, keeping them within `limit`
+    fn scale(min: &mut f32, max: &mut f32, factor: f32, limit: f32) {
+        if *max * factor <= limit {
+            *min *= factor;
+            *max *= factor;
+        }
+    }
+    fn adjust(&mut self, up: bool, color: &mut Color, out: &mut Readout) {
+        let factor = if up { 1.1 } else { 1.0 / 1.1 };
+        match self.control {
+            Control::Palette => {
+                self.palette = if up {
+                    self.palette.next()
+                } else {
+                    self.palette.prev()
+                };
+                out.show(format_args!("Palette: {:?}", self.palette));
+            }
+            Control::Fuel => {
+                let limit = self.heat_max;
+                Self::scale(&mut self.fuel_min, &mut self.fuel_max, factor, limit);
+                out.show(format_args!("Fuel: {} - {}", self.fuel_min, self.fuel_max));
+            }
+            Control::Cool => {
+                Self::scale(&mut self.cool_min, &mut self.cool_max, factor, 0.5);
+                out.show(format_args!("Cool: {} - {}", self.cool_min, self.cool_max));
+            }
+            Control::Flow => {
+                Self::scale(&mut self.flow_min, &mut self.flow_max, factor, 1.0);
+                out.show(format_args!("Flow: {} - {}", self.flow_min, self.flow_max));
+            }
+            Control::Hue => {
+                *color = color.shift_hue(if up { 2.0 } else { -2.0 });
+                out.show(format_args!("Hue: {}", color.hue.to_positive_degrees()));
+            }
+        }
+    }
     fn debug_dump(&self) {
         if let Some(top) = T::index_top() {
             for (row_count, row) in top.iter_down().enumerate() {
@@ -146,20 +262,12 @@ where
 
     fn render(&self, color: Color, model: &mut T) {
         for idx in model.iter_pixels() {
-            let val = self.cells[idx.usize()].max(0.0).min(1.0);
-            //let val = val * val;
-            //*model.get_mut(idx) = color.darken(1.0 - val).desaturate(1.0 - val).clamp();
-            //*model.get_mut(idx) = color.darken(val).desaturate(1.0 - val).clamp();
-            if val > self.heat_min {
-                *model.get_mut(idx) = Color {
-                    l: color.l * val,
-                    chroma: color.chroma * val,
-                    ..color
-                };
+            let val = self.cells[idx.usize()].clamp(0.0, 1.0);
+            *model.get_mut(idx) = if val > self.heat_min {
+                self.heat_color(val, color)
             } else {
-                *model.get_mut(idx) = Color::new(0.0, 0.0, 0.0);
-            }
-            *model.get_mut(idx) = color.shift_hue(self.hue_shift * (1.0 - val));
+                Color::new(0.0, 0.0, 0.0)
+            };
         }
     }
 
@@ -170,15 +278,16 @@ where
         }
     }
 
-    fn rotate_cw(&mut self, color: &mut Color) {
-        *color = color.shift_hue(2.0);
-        rprintln!("Hue: {}", color.hue.to_positive_degrees());
+    fn rotate_cw(&mut self, color: &mut Color, out: &mut Readout) {
+        self.adjust(true, color, out);
     }
 
-    fn rotate_ccw(&mut self, color: &mut Color) {
-        *color = color.shift_hue(-2.0);
-        rprintln!("Hue: {}", color.hue.to_positive_degrees());
+    fn rotate_ccw(&mut self, color: &mut Color, out: &mut Readout) {
+        self.adjust(false, color, out);
     }
 
-    //fn click(&mut self, color: &mut Color) {}
+    fn click(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.control = self.control.next();
+        out.show(format_args!("Set: {:?}", self.control));
+    }
 }
@@ -0,0 +1,164 @@
+use crate::{
+    effect::{Readout, TICKS_PER_SECOND},
+    math::{smoothstep, Vec3},
+    Color, Effect, PixelIndexable,
+};
+use core::marker::PhantomData;
+use palette::Mix;
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+const MAX_BLOBS: usize = 6;
+// Hues of the wax and the liquid around it
+const PALETTES: [(f32, f32); 4] = [(30.0, 330.0), (60.0, 260.0), (140.0, 220.0), (0.0, 90.0)];
+// Acceleration per unit of temperature away from neutral, in cube half-widths per second squared
+const BUOYANCY: f32 = 0.15;
+// Fraction of velocity lost per second
+const DRAG: f32 = 0.5;
+// Rate blobs take on the temperature of the heater or the cool top, per second
+const HEAT_RATE: f32 = 0.2;
+
+#[derive(Clone, Copy, Debug)]
+pub enum Control {
+    Count,
+    Colors,
+}
+
+impl Control {
+    pub fn next(self) -> Self {
+        use Control::*;
+        match self {
+            Count => Colors,
+            Colors => Count,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Blob {
+    pos: Vec3,
+    vel: Vec3,
+    // From 0.0 (cold, sinks) to 1.0 (hot, rises)
+    temp: f32,
+    radius: f32,
+}
+
+/// Lava lamp: blobs of wax warm at the bottom, float up, cool off at the top and sink again.
+/// They are drawn as metaballs sampled at each pixel's position on the cube.
+pub struct Lava<T: PixelIndexable> {
+    blobs: [Blob; MAX_BLOBS],
+    count: usize,
+    palette: usize,
+    control: Control,
+    rng: SmallRng,
+    _pd: PhantomData<T>,
+}
+
+impl<T: PixelIndexable> Lava<T> {
+    pub fn new(count: usize, palette: usize) -> Self {
+        let mut rng = SmallRng::seed_from_u64(1234);
+        let mut blobs = [Blob {
+            pos: Vec3::zero(),
+            vel: Vec3::zero(),
+            temp: 0.0,
+            radius: 0.0,
+        }; MAX_BLOBS];
+        for blob in blobs.iter_mut() {
+            blob.pos = Vec3::new(
+                rng.gen_range(-0.7, 0.7),
+                rng.gen_range(-0.7, 0.7),
+                rng.gen_range(-1.0, 1.0),
+            );
+            blob.temp = rng.gen_range(0.0, 1.0);
+            blob.radius = rng.gen_range(0.35, 0.6);
+        }
+        Self {
+            blobs,
+            count: count.clamp(1, MAX_BLOBS),
+            palette: palette % PALETTES.len(),
+            control: Control::Count,
+            rng,
+            _pd: PhantomData,
+        }
+    }
+
+    /// Metaball field at a point; the wax surface is where it reaches 1.0
+    fn field(&self, pos: Vec3) -> f32 {
+        self.blobs[..self.count]
+            .iter()
+            .map(|b| b.radius * b.radius / (pos - b.pos).length_squared().max(0.01))
+            .sum()
+    }
+
+    fn adjust(&mut self, up: bool, out: &mut Readout) {
+        match self.control {
+            Control::Count => {
+                self.count = if up {
+                    (self.count + 1).min(MAX_BLOBS)
+                } else {
+                    (self.count - 1).max(1)
+                };
+                out.show(format_args!("Blobs: {}", self.count));
+            }
+            Control::Colors => {
+                self.palette =
+                    (self.palette + if up { 1 } else { PALETTES.len() - 1 }) % PALETTES.len();
+                out.show(format_args!("Colors: {}", self.palette));
+            }
+        }
+    }
+}
+
+impl<T: PixelIndexable> Default for Lava<T> {
+    fn default() -> Self {
+        Self::new(4, 0)
+    }
+}
+
+impl<T: PixelIndexable> Effect<T> for Lava<T> {
+    fn tick(&mut self, _color: &mut Color) {
+        let dt = 1.0 / TICKS_PER_SECOND as f32;
+        let rng = &mut self.rng;
+        for blob in self.blobs[..self.count].iter_mut() {
+            // The heater warms the bottom third of the lamp and the top third is cool
+            let target = 1.0 - smoothstep(-0.6, 0.6, blob.pos.z);
+            blob.temp += (target - blob.temp) * HEAT_RATE * dt;
+            blob.vel.z += (blob.temp - 0.5) * BUOYANCY * dt;
+            // A little sideways wander so blobs don't keep to the same track
+            blob.vel.x += rng.gen_range(-0.02, 0.02) * dt;
+            blob.vel.y += rng.gen_range(-0.02, 0.02) * dt;
+            blob.vel = blob.vel * (1.0 - DRAG * dt);
+            blob.pos += blob.vel * dt;
+            // Keep the blobs inside the lamp
+            blob.pos.x = blob.pos.x.clamp(-0.8, 0.8);
+            blob.pos.y = blob.pos.y.clamp(-0.8, 0.8);
+            if blob.pos.z.abs() > 1.0 {
+                blob.pos.z = blob.pos.z.clamp(-1.0, 1.0);
+                blob.vel.z = 0.0;
+            }
+        }
+    }
+
+    fn render(&self, color: Color, model: &mut T) {
+        let (wax, liquid) = PALETTES[self.palette];
+        let wax = Color::new(color.l, color.chroma, wax);
+        let liquid = Color::new(color.l * 0.3, color.chroma * 0.6, liquid);
+        for idx in model.iter_pixels() {
+            let (x, y, z) = idx.as_cube_xyz();
+            let t = smoothstep(0.7, 1.3, self.field(Vec3::new(x, y, z)));
+            *model.get_mut(idx) = liquid.mix(&wax, t);
+        }
+    }
+
+    fn rotate_cw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.adjust(true, out);
+    }
+
+    fn rotate_ccw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.adjust(false, out);
+    }
+
+    fn click(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.control = self.control.next();
+        out.show(format_args!("Set: {:?}", self.control));
+    }
+}
@@ -0,0 +1,145 @@
+use crate::{
+    effect::{
+        particle::{Crawler, Heading},
+        Readout,
+    },
+    Color, Effect, PixelIndexable,
+};
+use core::marker::PhantomData;
+use generic_array::{ArrayLength, GenericArray};
+use palette::Mix;
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+const NUM_STREAMS: usize = 12;
+
+#[derive(Debug)]
+struct Stream<T> {
+    head: Crawler<T>,
+    // Pixels per tick
+    speed: f32,
+    progress: f32,
+}
+
+impl<T> Clone for Stream<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Stream<T> {}
+
+pub struct Matrix<T: PixelIndexable>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    // Brightness of the trail left at each pixel
+    trail: GenericArray<f32, T::SIZE>,
+    streams: [Option<Stream<T>>; NUM_STREAMS],
+    spawn_chance: f32,
+    // Fraction of trail brightness kept each tick
+    decay: f32,
+    speed: f32,
+    rng: SmallRng,
+    _pd: PhantomData<T>,
+}
+
+impl<T: PixelIndexable> Matrix<T>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    pub fn new(spawn_chance: f32, decay: f32, speed: f32) -> Self {
+        Self {
+            trail: GenericArray::default(),
+            streams: [None; NUM_STREAMS],
+            spawn_chance,
+            decay,
+            speed,
+            rng: SmallRng::seed_from_u64(1234),
+            _pd: PhantomData,
+        }
+    }
+
+    fn spawn(&mut self) {
+        let rng = &mut self.rng;
+        if let Some(slot) = self.streams.iter_mut().find(|s| s.is_none()) {
+            // Streams start anywhere on the top face and run out over the edges and down the
+            // sides, or start at the top of a side column
+            let height = if rng.gen_bool(0.5) {
+                rng.gen_range(5.0 / 8.0, 1.0)
+            } else {
+                4.0 / 8.0
+            };
+            let idx = T::spherical_to_index(rng.gen_range(0.0, 1.0), height);
+            let color = Color::new(100.0, 0.0, 0.0);
+            *slot = Some(Stream {
+                head: Crawler::new(idx, Heading::Down, u8::MAX, color),
+                speed: rng.gen_range(0.3, 1.0),
+                progress: 0.0,
+            });
+        }
+    }
+}
+
+impl<T: PixelIndexable> Default for Matrix<T>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    fn default() -> Self {
+        Self::new(0.3, 0.8, 1.0)
+    }
+}
+
+impl<T: PixelIndexable> Effect<T> for Matrix<T>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    fn tick(&mut self, _color: &mut Color) {
+        for level in self.trail.iter_mut() {
+            *level *= self.decay;
+        }
+        if self.rng.gen_bool(self.spawn_chance as f64) {
+            self.spawn();
+        }
+        for slot in self.streams.iter_mut() {
+            if let Some(stream) = slot {
+                stream.progress += stream.speed * self.speed;
+                while stream.progress >= 1.0 {
+                    stream.progress -= 1.0;
+                    // Leave a trail on every pixel passed, fast streams move several per tick
+                    self.trail[stream.head.idx.usize()] = 1.0;
+                    if !stream.head.step() {
+                        *slot = None;
+                        break;
+                    }
+                }
+            }
+            if let Some(stream) = slot {
+                self.trail[stream.head.idx.usize()] = 1.0;
+            }
+        }
+    }
+
+    fn render(&self, color: Color, model: &mut T) {
+        for idx in model.iter_pixels() {
+            let level = self.trail[idx.usize()];
+            *model.get_mut(idx) = Color {
+                l: color.l * level,
+                ..color
+            };
+        }
+        for stream in self.streams.iter().flatten() {
+            let head = model.get_mut(stream.head.idx);
+            *head = head.mix(&stream.head.color, 0.7);
+        }
+    }
+
+    fn rotate_cw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.speed = (self.speed * 1.2).min(4.0);
+        out.show(format_args!("Speed: {}", self.speed));
+    }
+
+    fn rotate_ccw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.speed = (self.speed / 1.2).max(0.1);
+        out.show(format_args!("Speed: {}", self.speed));
+    }
+}
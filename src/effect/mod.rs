@@ -1,31 +1,79 @@
-use crate::{Color, Direction, InputEvent, Knobs, PixelIndexable};
+use crate::{Color, Direction, InputEvent, Knobs, PixelIndexable, TimeOfDay};
 use generic_array::ArrayLength;
 use palette::{Limited, Shade};
 use rtt_target::rprintln;
 
+pub mod aurora;
+pub mod automata;
+pub mod boids;
+pub mod bombs;
+pub mod breath;
+pub mod candle;
+pub mod clock;
 pub mod cloud;
+pub mod daynight;
 pub mod drops;
+pub mod ecosystem;
 pub mod flame;
+pub mod lava;
+pub mod matrix;
+pub mod particle;
+pub mod plasma;
 pub mod rainbow;
+pub mod reaction;
+pub mod ripple;
+pub mod sand;
+pub mod seasons;
 pub mod solid;
 pub mod sparks;
+pub mod starfield;
 pub mod storm;
+pub mod text;
+pub mod waterfall;
 
+pub use aurora::Aurora;
+pub use automata::Automata;
+pub use boids::{Boids, NUM_BOIDS};
+pub use bombs::Bombs;
+pub use breath::Breath;
+pub use candle::Candle;
+pub use clock::Clock;
 pub use cloud::Cloud;
+pub use daynight::DayNight;
 pub use drops::Drops;
+pub use ecosystem::Ecosystem;
 pub use flame::Flame;
+pub use lava::Lava;
+pub use matrix::Matrix;
+pub use plasma::Plasma;
 pub use rainbow::Rainbow;
+pub use reaction::Reaction;
+pub use ripple::Ripple;
+pub use sand::Sand;
+pub use seasons::Seasons;
 pub use solid::Solid;
 pub use sparks::Sparks;
+pub use starfield::{Glitter, Starfield};
 pub use storm::Storm;
+pub use text::{Readout, Text};
+pub use waterfall::Waterfall;
+
+/// Rate at which effects are ticked; effects that run in real time units count ticks against this.
+pub const TICKS_PER_SECOND: u32 = 10;
 
 pub trait Effect<T: PixelIndexable> {
     fn tick(&mut self, color: &mut Color);
     fn render(&self, color: Color, model: &mut T);
-    fn rotate_cw(&mut self, _color: &mut Color) {}
-    fn rotate_ccw(&mut self, _color: &mut Color) {}
-    fn click(&mut self, _color: &mut Color) {}
+    /// Knob 3 handlers.  Anything written to `out` is shown on the lantern as feedback.
+    fn rotate_cw(&mut self, _color: &mut Color, _out: &mut Readout) {}
+    fn rotate_ccw(&mut self, _color: &mut Color, _out: &mut Readout) {}
+    fn click(&mut self, _color: &mut Color, _out: &mut Readout) {}
     fn init(&mut self, _model: &mut T) {}
+    /// Receive the current wall clock time.  Effects that let the time be set from the knobs
+    /// return the new time so it can be written back to the clock.
+    fn sync_time(&mut self, _now: TimeOfDay) -> Option<TimeOfDay> {
+        None
+    }
     // XXX TODO input / control channels
 }
 
@@ -37,7 +85,27 @@ where
     Rainbow(Rainbow<T>),
     Solid(Solid),
     Storm(Storm<T>),
-    //Sparks(Sparks<T>),
+    Waterfall(Waterfall<T>),
+    Bombs(Bombs<T>),
+    Breath(Breath),
+    Candle(Candle<T>),
+    DayNight(DayNight<T>),
+    Seasons(Seasons<T>),
+    Boids(Boids<T, NUM_BOIDS>),
+    Ecosystem(Ecosystem<T>),
+    Matrix(Matrix<T>),
+    Clock(Clock<T>),
+    Automata(Automata<T>),
+    Sparks(Sparks<T>),
+    Drops(Drops<T>),
+    Cloud(Cloud<T>),
+    Plasma(Plasma<T>),
+    Aurora(Aurora<T>),
+    Ripple(Ripple<T>),
+    Reaction(Reaction<T>),
+    Lava(Lava<T>),
+    Starfield(Starfield<T>),
+    Sand(Sand<T>),
 }
 
 impl<T: PixelIndexable> EffectCycle<T>
@@ -50,11 +118,31 @@ where
     }
     pub fn prev(&mut self) {
         match self {
-            EffectCycle::Flame(_) => *self = EffectCycle::Storm(Storm::default()),
+            EffectCycle::Flame(_) => *self = EffectCycle::Sand(Sand::default()),
             EffectCycle::Rainbow(_) => *self = EffectCycle::Flame(Flame::default()),
             EffectCycle::Solid(_) => *self = EffectCycle::Rainbow(Rainbow::default()),
             EffectCycle::Storm(_) => *self = EffectCycle::Solid(Solid::default()),
-            //EffectCycle::Sparks(_) => *self = EffectCycle::Storm(Storm::default()),
+            EffectCycle::Waterfall(_) => *self = EffectCycle::Storm(Storm::default()),
+            EffectCycle::Bombs(_) => *self = EffectCycle::Waterfall(Waterfall::default()),
+            EffectCycle::Breath(_) => *self = EffectCycle::Bombs(Bombs::default()),
+            EffectCycle::Candle(_) => *self = EffectCycle::Breath(Breath::default()),
+            EffectCycle::DayNight(_) => *self = EffectCycle::Candle(Candle::default()),
+            EffectCycle::Seasons(_) => *self = EffectCycle::DayNight(DayNight::default()),
+            EffectCycle::Boids(_) => *self = EffectCycle::Seasons(Seasons::default()),
+            EffectCycle::Ecosystem(_) => *self = EffectCycle::Boids(Boids::default()),
+            EffectCycle::Matrix(_) => *self = EffectCycle::Ecosystem(Ecosystem::default()),
+            EffectCycle::Clock(_) => *self = EffectCycle::Matrix(Matrix::default()),
+            EffectCycle::Automata(_) => *self = EffectCycle::Clock(Clock::default()),
+            EffectCycle::Sparks(_) => *self = EffectCycle::Automata(Automata::default()),
+            EffectCycle::Drops(_) => *self = EffectCycle::Sparks(Sparks::default()),
+            EffectCycle::Cloud(_) => *self = EffectCycle::Drops(Drops::default()),
+            EffectCycle::Plasma(_) => *self = EffectCycle::Cloud(Cloud::default()),
+            EffectCycle::Aurora(_) => *self = EffectCycle::Plasma(Plasma::default()),
+            EffectCycle::Ripple(_) => *self = EffectCycle::Aurora(Aurora::default()),
+            EffectCycle::Reaction(_) => *self = EffectCycle::Ripple(Ripple::default()),
+            EffectCycle::Lava(_) => *self = EffectCycle::Reaction(Reaction::default()),
+            EffectCycle::Starfield(_) => *self = EffectCycle::Lava(Lava::default()),
+            EffectCycle::Sand(_) => *self = EffectCycle::Starfield(Starfield::default()),
         }
     }
     pub fn next(&mut self) {
@@ -62,8 +150,28 @@ where
             EffectCycle::Flame(_) => *self = EffectCycle::Rainbow(Rainbow::default()),
             EffectCycle::Rainbow(_) => *self = EffectCycle::Solid(Solid::default()),
             EffectCycle::Solid(_) => *self = EffectCycle::Storm(Storm::default()),
-            EffectCycle::Storm(_) => *self = EffectCycle::Flame(Flame::default()),
-            //EffectCycle::Sparks(_) => *self = EffectCycle::Rainbow(Rainbow::default()),
+            EffectCycle::Storm(_) => *self = EffectCycle::Waterfall(Waterfall::default()),
+            EffectCycle::Waterfall(_) => *self = EffectCycle::Bombs(Bombs::default()),
+            EffectCycle::Bombs(_) => *self = EffectCycle::Breath(Breath::default()),
+            EffectCycle::Breath(_) => *self = EffectCycle::Candle(Candle::default()),
+            EffectCycle::Candle(_) => *self = EffectCycle::DayNight(DayNight::default()),
+            EffectCycle::DayNight(_) => *self = EffectCycle::Seasons(Seasons::default()),
+            EffectCycle::Seasons(_) => *self = EffectCycle::Boids(Boids::default()),
+            EffectCycle::Boids(_) => *self = EffectCycle::Ecosystem(Ecosystem::default()),
+            EffectCycle::Ecosystem(_) => *self = EffectCycle::Matrix(Matrix::default()),
+            EffectCycle::Matrix(_) => *self = EffectCycle::Clock(Clock::default()),
+            EffectCycle::Clock(_) => *self = EffectCycle::Automata(Automata::default()),
+            EffectCycle::Automata(_) => *self = EffectCycle::Sparks(Sparks::default()),
+            EffectCycle::Sparks(_) => *self = EffectCycle::Drops(Drops::default()),
+            EffectCycle::Drops(_) => *self = EffectCycle::Cloud(Cloud::default()),
+            EffectCycle::Cloud(_) => *self = EffectCycle::Plasma(Plasma::default()),
+            EffectCycle::Plasma(_) => *self = EffectCycle::Aurora(Aurora::default()),
+            EffectCycle::Aurora(_) => *self = EffectCycle::Ripple(Ripple::default()),
+            EffectCycle::Ripple(_) => *self = EffectCycle::Reaction(Reaction::default()),
+            EffectCycle::Reaction(_) => *self = EffectCycle::Lava(Lava::default()),
+            EffectCycle::Lava(_) => *self = EffectCycle::Starfield(Starfield::default()),
+            EffectCycle::Starfield(_) => *self = EffectCycle::Sand(Sand::default()),
+            EffectCycle::Sand(_) => *self = EffectCycle::Flame(Flame::default()),
         }
     }
     pub fn effect(&self) -> &dyn Effect<T> {
@@ -72,7 +180,27 @@ where
             EffectCycle::Rainbow(e) => e as &dyn Effect<T>,
             EffectCycle::Solid(e) => e as &dyn Effect<T>,
             EffectCycle::Storm(e) => e as &dyn Effect<T>,
-            //EffectCycle::Sparks(e) => e as &dyn Effect<T>,
+            EffectCycle::Waterfall(e) => e as &dyn Effect<T>,
+            EffectCycle::Bombs(e) => e as &dyn Effect<T>,
+            EffectCycle::Breath(e) => e as &dyn Effect<T>,
+            EffectCycle::Candle(e) => e as &dyn Effect<T>,
+            EffectCycle::DayNight(e) => e as &dyn Effect<T>,
+            EffectCycle::Seasons(e) => e as &dyn Effect<T>,
+            EffectCycle::Boids(e) => e as &dyn Effect<T>,
+            EffectCycle::Ecosystem(e) => e as &dyn Effect<T>,
+            EffectCycle::Matrix(e) => e as &dyn Effect<T>,
+            EffectCycle::Clock(e) => e as &dyn Effect<T>,
+            EffectCycle::Automata(e) => e as &dyn Effect<T>,
+            EffectCycle::Sparks(e) => e as &dyn Effect<T>,
+            EffectCycle::Drops(e) => e as &dyn Effect<T>,
+            EffectCycle::Cloud(e) => e as &dyn Effect<T>,
+            EffectCycle::Plasma(e) => e as &dyn Effect<T>,
+            EffectCycle::Aurora(e) => e as &dyn Effect<T>,
+            EffectCycle::Ripple(e) => e as &dyn Effect<T>,
+            EffectCycle::Reaction(e) => e as &dyn Effect<T>,
+            EffectCycle::Lava(e) => e as &dyn Effect<T>,
+            EffectCycle::Starfield(e) => e as &dyn Effect<T>,
+            EffectCycle::Sand(e) => e as &dyn Effect<T>,
         }
     }
     pub fn effect_mut(&mut self) -> &mut dyn Effect<T> {
@@ -81,7 +209,27 @@ where
             EffectCycle::Rainbow(e) => e as &mut dyn Effect<T>,
             EffectCycle::Solid(e) => e as &mut dyn Effect<T>,
             EffectCycle::Storm(e) => e as &mut dyn Effect<T>,
-            //EffectCycle::Sparks(e) => e as &mut dyn Effect<T>,
+            EffectCycle::Waterfall(e) => e as &mut dyn Effect<T>,
+            EffectCycle::Bombs(e) => e as &mut dyn Effect<T>,
+            EffectCycle::Breath(e) => e as &mut dyn Effect<T>,
+            EffectCycle::Candle(e) => e as &mut dyn Effect<T>,
+            EffectCycle::DayNight(e) => e as &mut dyn Effect<T>,
+            EffectCycle::Seasons(e) => e as &mut dyn Effect<T>,
+            EffectCycle::Boids(e) => e as &mut dyn Effect<T>,
+            EffectCycle::Ecosystem(e) => e as &mut dyn Effect<T>,
+            EffectCycle::Matrix(e) => e as &mut dyn Effect<T>,
+            EffectCycle::Clock(e) => e as &mut dyn Effect<T>,
+            EffectCycle::Automata(e) => e as &mut dyn Effect<T>,
+            EffectCycle::Sparks(e) => e as &mut dyn Effect<T>,
+            EffectCycle::Drops(e) => e as &mut dyn Effect<T>,
+            EffectCycle::Cloud(e) => e as &mut dyn Effect<T>,
+            EffectCycle::Plasma(e) => e as &mut dyn Effect<T>,
+            EffectCycle::Aurora(e) => e as &mut dyn Effect<T>,
+            EffectCycle::Ripple(e) => e as &mut dyn Effect<T>,
+            EffectCycle::Reaction(e) => e as &mut dyn Effect<T>,
+            EffectCycle::Lava(e) => e as &mut dyn Effect<T>,
+            EffectCycle::Starfield(e) => e as &mut dyn Effect<T>,
+            EffectCycle::Sand(e) => e as &mut dyn Effect<T>,
         }
     }
     pub fn name(&self) -> &'static str {
@@ -90,7 +238,27 @@ where
             EffectCycle::Rainbow(_) => "Rainbow",
             EffectCycle::Solid(_) => "Solid",
             EffectCycle::Storm(_) => "Storm",
-            //EffectCycle::Sparks(_) => "Sparks",
+            EffectCycle::Waterfall(_) => "Waterfall",
+            EffectCycle::Bombs(_) => "Bombs",
+            EffectCycle::Breath(_) => "Breath",
+            EffectCycle::Candle(_) => "Candle",
+            EffectCycle::DayNight(_) => "DayNight",
+            EffectCycle::Seasons(_) => "Seasons",
+            EffectCycle::Boids(_) => "Boids",
+            EffectCycle::Ecosystem(_) => "Ecosystem",
+            EffectCycle::Matrix(_) => "Matrix",
+            EffectCycle::Clock(_) => "Clock",
+            EffectCycle::Automata(_) => "Automata",
+            EffectCycle::Sparks(_) => "Sparks",
+            EffectCycle::Drops(_) => "Drops",
+            EffectCycle::Cloud(_) => "Cloud",
+            EffectCycle::Plasma(_) => "Plasma",
+            EffectCycle::Aurora(_) => "Aurora",
+            EffectCycle::Ripple(_) => "Ripple",
+            EffectCycle::Reaction(_) => "Reaction",
+            EffectCycle::Lava(_) => "Lava",
+            EffectCycle::Starfield(_) => "Starfield",
+            EffectCycle::Sand(_) => "Sand",
         }
     }
 }
@@ -99,16 +267,16 @@ impl<T: PixelIndexable> Effect<T> for EffectCycle<T>
 where
     T::SIZE: ArrayLength<f32>,
 {
-    fn rotate_cw(&mut self, color: &mut Color) {
-        self.effect_mut().rotate_cw(color)
+    fn rotate_cw(&mut self, color: &mut Color, out: &mut Readout) {
+        self.effect_mut().rotate_cw(color, out)
     }
 
-    fn rotate_ccw(&mut self, color: &mut Color) {
-        self.effect_mut().rotate_ccw(color)
+    fn rotate_ccw(&mut self, color: &mut Color, out: &mut Readout) {
+        self.effect_mut().rotate_ccw(color, out)
     }
 
-    fn click(&mut self, color: &mut Color) {
-        self.effect_mut().click(color)
+    fn click(&mut self, color: &mut Color, out: &mut Readout) {
+        self.effect_mut().click(color, out)
     }
 
     fn tick(&mut self, color: &mut Color) {
@@ -118,6 +286,10 @@ where
     fn render(&self, color: Color, model: &mut T) {
         self.effect().render(color, model)
     }
+
+    fn sync_time(&mut self, now: TimeOfDay) -> Option<TimeOfDay> {
+        self.effect_mut().sync_time(now)
+    }
 }
 
 pub struct EffectManager<T: PixelIndexable>
@@ -126,6 +298,10 @@ where
 {
     pub ec: EffectCycle<T>,
     pub color: Color,
+    // Flashes of the current colour over the running effect, toggled from knob 2
+    pub glitter: Option<Glitter<T>>,
+    // Messages scrolled over the running effect
+    pub text: Text<T>,
 }
 
 impl<T: PixelIndexable> EffectManager<T>
@@ -135,47 +311,89 @@ where
     pub fn default() -> Self {
         let ec = EffectCycle::new();
         let color = Color::new(30.0, 120.0, 36.0);
-        Self { ec, color }
+        Self {
+            ec,
+            color,
+            glitter: None,
+            text: Text::default(),
+        }
     }
 
     pub fn tick(&mut self) {
-        self.ec.tick(&mut self.color)
+        self.ec.tick(&mut self.color);
+        if let Some(glitter) = &mut self.glitter {
+            glitter.tick(&mut self.color);
+        }
+        self.text.tick(&mut self.color);
     }
 
+    pub fn sync_time(&mut self, now: TimeOfDay) -> Option<TimeOfDay> {
+        self.ec.sync_time(now)
+    }
+
+    /// Draw the running effect into `model`, which effects may read back as their state
     pub fn render(&self, model: &mut T) {
         // TODO Here's where we should apply input feedback
-        self.ec.render(self.color, model)
+        self.ec.render(self.color, model);
+    }
+
+    /// Lay the overlays over a copy of the rendered model, so they never feed back into the
+    /// state of effects that fade or trail the previous frame
+    pub fn composite(&self, model: &T) -> T
+    where
+        T: Clone,
+    {
+        let mut frame = model.clone();
+        if let Some(glitter) = &self.glitter {
+            glitter.render(self.color, &mut frame);
+        }
+        self.text.render(self.color, &mut frame);
+        frame
     }
 
     pub fn handle_event(&mut self, event: InputEvent) {
         use Direction::*;
         use InputEvent::*;
         use Knobs::*;
+        let mut out = Readout::new();
         match event {
             Press(Knob1) => {
                 self.ec.next();
-                rprintln!("{}", self.ec.name());
+                out.show(format_args!("{}", self.ec.name()));
             }
             Spin(Knob1, dir) => {
                 self.color = self.color.lighten(dir * 0.02).clamp();
-                rprintln!("Luma: {}", self.color.l);
+                out.show(format_args!("Luma: {}", self.color.l));
+            }
+            Press(Knob2) => {
+                self.glitter = match self.glitter {
+                    Some(_) => None,
+                    None => Some(Glitter::default()),
+                };
+                out.show(format_args!("Glitter: {}", self.glitter.is_some()));
             }
             Spin(Knob2, dir) => {
                 self.color.chroma += dir * 2.0;
                 self.color.clamp_self();
-                rprintln!("Chroma: {}", self.color.chroma);
+                out.show(format_args!("Chroma: {}", self.color.chroma));
             }
             Spin(Knob3, Clockwise) => {
-                self.ec.rotate_cw(&mut self.color);
+                self.ec.rotate_cw(&mut self.color, &mut out);
             }
             Spin(Knob3, CounterClockwise) => {
-                self.ec.rotate_ccw(&mut self.color);
+                self.ec.rotate_ccw(&mut self.color, &mut out);
             }
             Press(Knob3) => {
-                self.ec.click(&mut self.color);
+                self.ec.click(&mut self.color, &mut out);
             }
             Release(Knob3) => {}
             _ => {}
         }
+        // Feedback goes to the debug console and scrolls across the lantern
+        if !out.is_empty() {
+            rprintln!("{}", out.as_str());
+            self.text
+                .show(format_args!("{}", out.as_str()), text::Mode::Once);
+        }
     }
 }
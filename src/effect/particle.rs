@@ -0,0 +1,138 @@
+use crate::{math::wrap, Color, PixelIndex, PixelIndexable};
+
+/// A point moving freely over the model in spherical coordinates.
+///
+/// `dir` wraps around the sides, `height` runs from the bottom of the sides (0.0) to the
+/// centre of the top face (1.0).  A particle with no `life` left is dead and its slot can be
+/// reused.
+#[derive(Copy, Clone, Debug)]
+pub struct Particle {
+    pub dir: f32,
+    pub height: f32,
+    pub ddir: f32,
+    pub dheight: f32,
+    pub color: Color,
+    pub life: f32,
+}
+
+impl Particle {
+    pub fn new(dir: f32, height: f32, ddir: f32, dheight: f32, color: Color) -> Self {
+        Self {
+            dir,
+            height,
+            ddir,
+            dheight,
+            color,
+            life: 1.0,
+        }
+    }
+    pub fn dead() -> Self {
+        Self {
+            life: 0.0,
+            ..Self::new(0.0, 0.0, 0.0, 0.0, Color::new(0.0, 0.0, 0.0))
+        }
+    }
+    pub fn is_alive(&self) -> bool {
+        self.life > 0.0
+    }
+    pub fn kill(&mut self) {
+        self.life = 0.0;
+    }
+    /// Advance one tick, pulling down by `gravity` and slowing by the `drag` fraction.
+    pub fn tick(&mut self, gravity: f32, drag: f32) {
+        self.dir = wrap(self.dir + self.ddir);
+        self.height += self.dheight;
+        self.dheight -= gravity;
+        self.ddir *= 1.0 - drag;
+        self.dheight *= 1.0 - drag;
+    }
+    pub fn in_bounds(&self) -> bool {
+        self.height >= 0.0 && self.height <= 1.0
+    }
+    pub fn index<T: PixelIndexable>(&self) -> PixelIndex<T> {
+        T::spherical_to_index(self.dir, self.height)
+    }
+    pub fn render<T: PixelIndexable>(&self, model: &mut T) {
+        if self.is_alive() && self.in_bounds() {
+            *model.get_mut(self.index()) = self.color;
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Heading {
+    Up,
+    Down,
+    Left,
+    Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
+}
+
+impl Heading {
+    pub fn step<T: PixelIndexable>(self, idx: PixelIndex<T>) -> Option<PixelIndex<T>> {
+        use Heading::*;
+        match self {
+            Up => idx.up(),
+            Down => idx.down(),
+            Left => idx.left(),
+            Right => idx.right(),
+            UpLeft => idx.up().and_then(PixelIndex::left),
+            UpRight => idx.up().and_then(PixelIndex::right),
+            DownLeft => idx.down().and_then(PixelIndex::left),
+            DownRight => idx.down().and_then(PixelIndex::right),
+        }
+    }
+}
+
+/// A particle that crawls from pixel to pixel along the model's neighbours, so it follows the
+/// surface across face edges.
+#[derive(Debug)]
+pub struct Crawler<T> {
+    pub idx: PixelIndex<T>,
+    pub heading: Heading,
+    pub steps: u8,
+    pub color: Color,
+}
+
+impl<T> Clone for Crawler<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Crawler<T> {}
+
+impl<T: PixelIndexable> Crawler<T> {
+    pub fn new(idx: PixelIndex<T>, heading: Heading, steps: u8, color: Color) -> Self {
+        Self {
+            idx,
+            heading,
+            steps,
+            color,
+        }
+    }
+    pub fn is_alive(&self) -> bool {
+        self.steps > 0
+    }
+    /// Move one pixel along `heading`.  Returns false once the crawler has run out of steps or
+    /// walked off the edge of the model.
+    pub fn step(&mut self) -> bool {
+        if self.steps == 0 {
+            return false;
+        }
+        match self.heading.step(self.idx) {
+            Some(next) => {
+                self.idx = next;
+                self.steps -= 1;
+                true
+            }
+            None => {
+                self.steps = 0;
+                false
+            }
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use crate::{
+    effect::{Readout, TICKS_PER_SECOND},
+    Color, Effect, PixelIndexable,
+};
+use core::{f32::consts::PI, marker::PhantomData};
+use num_traits::Float;
+use palette::Hue;
+
+#[derive(Clone, Copy, Debug)]
+pub enum Control {
+    Scale,
+    Speed,
+    Rotation,
+}
+
+impl Control {
+    pub fn next(self) -> Self {
+        use Control::*;
+        match self {
+            Scale => Speed,
+            Speed => Rotation,
+            Rotation => Scale,
+        }
+    }
+}
+
+/// Demoscene plasma: a handful of sine fields over the pixels' positions on the cube, summed
+/// and used to pick a hue.  Working in 3D rather than per face keeps the blobs flowing
+/// smoothly over the edges.
+pub struct Plasma<T: PixelIndexable> {
+    // Time in seconds, scaled by speed
+    time: f32,
+    // Spatial frequency of the fields, in radians per cube half-width
+    scale: f32,
+    speed: f32,
+    // Palette rotation, in degrees per second
+    rotation: f32,
+    hue: f32,
+    control: Control,
+    _pd: PhantomData<T>,
+}
+
+impl<T: PixelIndexable> Plasma<T> {
+    pub fn new(scale: f32, speed: f32, rotation: f32) -> Self {
+        Self {
+            time: 0.0,
+            scale,
+            speed,
+            rotation,
+            hue: 0.0,
+            control: Control::Scale,
+            _pd: PhantomData,
+        }
+    }
+
+    /// Sum of the fields at a point, from -1.0 to 1.0
+    fn field(&self, x: f32, y: f32, z: f32) -> f32 {
+        let s = self.scale;
+        let t = self.time;
+        // A wandering centre for the ring field
+        let (cx, cy) = ((t * 0.31).sin(), (t * 0.23).cos());
+        let (dx, dy) = (x - cx, y - cy);
+        let r = (dx * dx + dy * dy + z * z).sqrt();
+        let sum = (x * s + t).sin()
+            + (y * s * 1.3 - t * 0.7).sin()
+            + ((x + y + z) * s * 0.7 + t * 1.1).sin()
+            + (r * s * 1.5 - t).sin();
+        sum / 4.0
+    }
+
+    fn adjust(&mut self, up: bool, out: &mut Readout) {
+        let factor = if up { 1.2 } else { 1.0 / 1.2 };
+        match self.control {
+            Control::Scale => {
+                self.scale = (self.scale * factor).clamp(0.5, 10.0);
+                out.show(format_args!("Scale: {}", self.scale));
+            }
+            Control::Speed => {
+                self.speed = (self.speed * factor).clamp(0.05, 5.0);
+                out.show(format_args!("Speed: {}", self.speed));
+            }
+            Control::Rotation => {
+                self.rotation += if up { 5.0 } else { -5.0 };
+                out.show(format_args!("Rotation: {}", self.rotation));
+            }
+        }
+    }
+}
+
+impl<T: PixelIndexable> Default for Plasma<T> {
+    fn default() -> Self {
+        Self::new(2.0, 0.5, 10.0)
+    }
+}
+
+impl<T: PixelIndexable> Effect<T> for Plasma<T> {
+    fn tick(&mut self, _color: &mut Color) {
+        let dt = 1.0 / TICKS_PER_SECOND as f32;
+        self.time += self.speed * dt;
+        self.hue = (self.hue + self.rotation * dt) % 360.0;
+    }
+
+    fn render(&self, color: Color, model: &mut T) {
+        for idx in model.iter_pixels() {
+            let (x, y, z) = idx.as_cube_xyz();
+            let v = self.field(x, y, z);
+            // Sweep half the colour wheel across the field, dimming in the troughs
+            let l = color.l * (0.6 + 0.4 * (v * PI).cos());
+            *model.get_mut(idx) = Color { l, ..color }.shift_hue(self.hue + 180.0 * v);
+        }
+    }
+
+    fn rotate_cw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.adjust(true, out);
+    }
+
+    fn rotate_ccw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.adjust(false, out);
+    }
+
+    fn click(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.control = self.control.next();
+        out.show(format_args!("Set: {:?}", self.control));
+    }
+}
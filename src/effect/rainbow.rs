@@ -1,4 +1,4 @@
-use crate::{Color, Effect, PixelIndexable};
+use crate::{effect::Readout, Color, Effect, PixelIndexable};
 use core::marker::PhantomData;
 use palette::Hue;
 
@@ -59,15 +59,15 @@ impl<T: PixelIndexable> Effect<T> for Rainbow<T> {
             };
         }
     }
-    fn rotate_cw(&mut self, _color: &mut Color) {
+    fn rotate_cw(&mut self, _color: &mut Color, _out: &mut Readout) {
         //self.color = self.color.shift_hue(self.speed);
         self.speed *= 1.1;
     }
-    fn rotate_ccw(&mut self, _color: &mut Color) {
+    fn rotate_ccw(&mut self, _color: &mut Color, _out: &mut Readout) {
         //self.color = self.color.shift_hue(self.speed * -1.0);
         self.speed *= 0.9;
     }
-    fn click(&mut self, _color: &mut Color) {
+    fn click(&mut self, _color: &mut Color, _out: &mut Readout) {
         self.orient = self.orient.next();
     }
 }
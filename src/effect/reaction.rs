@@ -0,0 +1,186 @@
+use crate::{
+    effect::{particle::Heading, Readout},
+    math::smoothstep,
+    pixelindex::PixelIterator,
+    Color, Effect, PixelIndex, PixelIndexable,
+};
+use core::marker::PhantomData;
+use generic_array::{ArrayLength, GenericArray};
+use num_traits::Float;
+use palette::Hue;
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+use typenum::Unsigned;
+
+const NEIGHBOURS: [Heading; 4] = [Heading::Up, Heading::Down, Heading::Left, Heading::Right];
+// Diffusion rates of the two chemicals
+const DIFFUSE_U: f32 = 1.0;
+const DIFFUSE_V: f32 = 0.5;
+// Simulation steps per tick
+const STEPS: usize = 4;
+// Patches of V dropped in when seeding
+const SEEDS: usize = 6;
+
+// Name, feed and kill rates
+const PRESETS: [(&str, f32, f32); 4] = [
+    ("Spots", 0.030, 0.062),
+    ("Stripes", 0.022, 0.051),
+    ("Coral", 0.0545, 0.062),
+    ("Mitosis", 0.0367, 0.0649),
+];
+
+/// Gray-Scott reaction-diffusion: chemical V feeds on U and spreads into spots, stripes and
+/// branching growth depending on the feed and kill rates.
+pub struct Reaction<T: PixelIndexable>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    u: GenericArray<f32, T::SIZE>,
+    v: GenericArray<f32, T::SIZE>,
+    preset: usize,
+    // Rate U is replenished
+    feed: f32,
+    // Rate V is removed
+    kill: f32,
+    // Distance of kill below the edge of the pattern forming region, held while feed is adjusted
+    margin: f32,
+    rng: SmallRng,
+    _pd: PhantomData<T>,
+}
+
+impl<T: PixelIndexable> Reaction<T>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    pub fn new(preset: usize) -> Self {
+        let mut rv = Self {
+            u: GenericArray::default(),
+            v: GenericArray::default(),
+            preset: 0,
+            feed: 0.0,
+            kill: 0.0,
+            margin: 0.0,
+            rng: SmallRng::seed_from_u64(1234),
+            _pd: PhantomData,
+        };
+        rv.select(preset);
+        rv
+    }
+
+    fn select(&mut self, preset: usize) {
+        self.preset = preset % PRESETS.len();
+        let (_, feed, kill) = PRESETS[self.preset];
+        self.feed = feed;
+        self.kill = kill;
+        self.margin = Self::boundary(feed) - kill;
+        self.reseed();
+    }
+
+    /// Kill rate at the edge of the region where patterns form, for a given feed rate
+    fn boundary(feed: f32) -> f32 {
+        feed.sqrt() / 2.0 - feed
+    }
+
+    fn reseed(&mut self) {
+        for (u, v) in self.u.iter_mut().zip(self.v.iter_mut()) {
+            *u = 1.0;
+            *v = 0.0;
+        }
+        for _ in 0..SEEDS {
+            let idx: PixelIndex<T> = self.rng.gen_range(0, T::SIZE::to_usize()).into();
+            for n in NEIGHBOURS
+                .iter()
+                .filter_map(|h| h.step(idx))
+                .chain(Some(idx))
+            {
+                self.u[n.usize()] = 0.5;
+                self.v[n.usize()] = 0.25;
+            }
+        }
+    }
+
+    /// Mean of the neighbours less the pixel itself
+    fn laplacian(cells: &GenericArray<f32, T::SIZE>, idx: PixelIndex<T>) -> f32 {
+        let here = cells[idx.usize()];
+        let (sum, count) = NEIGHBOURS
+            .iter()
+            .filter_map(|h| h.step(idx))
+            .fold((0.0, 0), |(sum, count), n| {
+                (sum + cells[n.usize()], count + 1)
+            });
+        if count > 0 {
+            sum / count as f32 - here
+        } else {
+            0.0
+        }
+    }
+
+    fn step(&mut self) {
+        let mut u: GenericArray<f32, T::SIZE> = GenericArray::default();
+        let mut v: GenericArray<f32, T::SIZE> = GenericArray::default();
+        let px_iter: PixelIterator<T> = PixelIterator::all();
+        for idx in px_iter {
+            let i = idx.usize();
+            let (a, b) = (self.u[i], self.v[i]);
+            let reaction = a * b * b;
+            u[i] = a + DIFFUSE_U * Self::laplacian(&self.u, idx) - reaction + self.feed * (1.0 - a);
+            v[i] = b + DIFFUSE_V * Self::laplacian(&self.v, idx) + reaction
+                - (self.feed + self.kill) * b;
+        }
+        self.u = u;
+        self.v = v;
+    }
+
+    fn adjust(&mut self, delta: f32, out: &mut Readout) {
+        self.feed = (self.feed + delta).clamp(0.01, 0.1);
+        self.kill = Self::boundary(self.feed) - self.margin;
+        out.show(format_args!("Feed: {} Kill: {}", self.feed, self.kill));
+    }
+}
+
+impl<T: PixelIndexable> Default for Reaction<T>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl<T: PixelIndexable> Effect<T> for Reaction<T>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    fn tick(&mut self, _color: &mut Color) {
+        for _ in 0..STEPS {
+            self.step();
+        }
+        if self.v.iter().all(|&v| v < 0.01) {
+            self.reseed();
+        }
+    }
+
+    fn render(&self, color: Color, model: &mut T) {
+        for idx in model.iter_pixels() {
+            let v = self.v[idx.usize()];
+            let t = smoothstep(0.05, 0.35, v);
+            *model.get_mut(idx) = Color {
+                l: color.l * (0.1 + 0.9 * t),
+                ..color
+            }
+            .shift_hue(60.0 * t);
+        }
+    }
+
+    fn rotate_cw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.adjust(0.001, out);
+    }
+
+    fn rotate_ccw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.adjust(-0.001, out);
+    }
+
+    fn click(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.select(self.preset + 1);
+        out.show(format_args!("{}", PRESETS[self.preset].0));
+    }
+}
@@ -0,0 +1,146 @@
+use crate::{
+    effect::{particle::Heading, Readout},
+    math::smoothstep,
+    pixelindex::PixelIterator,
+    Color, Effect, PixelIndex, PixelIndexable,
+};
+use core::marker::PhantomData;
+use generic_array::{ArrayLength, GenericArray};
+use palette::Mix;
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+use typenum::Unsigned;
+
+const NEIGHBOURS: [Heading; 4] = [Heading::Up, Heading::Down, Heading::Left, Heading::Right];
+
+/// Waves on a water surface, from the discrete wave equation run over the pixel neighbours so
+/// they carry on around the edges of the model.
+pub struct Ripple<T: PixelIndexable>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    // Surface height this tick and last tick
+    height: GenericArray<f32, T::SIZE>,
+    prev: GenericArray<f32, T::SIZE>,
+    // Fraction of the wave's motion kept each tick
+    damping: f32,
+    // Wave speed squared, in pixels per tick; must stay below 1.0 to keep the simulation stable
+    tension: f32,
+    // Chance per tick of a raindrop landing
+    rain: f32,
+    rng: SmallRng,
+    _pd: PhantomData<T>,
+}
+
+impl<T: PixelIndexable> Ripple<T>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    pub fn new(damping: f32, tension: f32, rain: f32) -> Self {
+        Self {
+            height: GenericArray::default(),
+            prev: GenericArray::default(),
+            damping,
+            tension,
+            rain,
+            rng: SmallRng::seed_from_u64(1234),
+            _pd: PhantomData,
+        }
+    }
+
+    fn splash(&mut self, idx: PixelIndex<T>, depth: f32) {
+        self.height[idx.usize()] -= depth;
+    }
+
+    fn random_index(&mut self) -> PixelIndex<T> {
+        self.rng.gen_range(0, T::SIZE::to_usize()).into()
+    }
+
+    /// Mean height of the pixel's neighbours
+    fn neighbours(&self, idx: PixelIndex<T>) -> f32 {
+        let (sum, count) = NEIGHBOURS
+            .iter()
+            .filter_map(|h| h.step(idx))
+            .fold((0.0, 0), |(sum, count), n| {
+                (sum + self.height[n.usize()], count + 1)
+            });
+        if count > 0 {
+            sum / count as f32
+        } else {
+            self.height[idx.usize()]
+        }
+    }
+
+    /// Steepness of the surface at a pixel, for picking out highlights
+    fn slope(&self, idx: PixelIndex<T>) -> f32 {
+        let h = self.height[idx.usize()];
+        NEIGHBOURS
+            .iter()
+            .filter_map(|d| d.step(idx))
+            .map(|n| (self.height[n.usize()] - h).abs())
+            .fold(0.0, f32::max)
+    }
+}
+
+impl<T: PixelIndexable> Default for Ripple<T>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    fn default() -> Self {
+        Self::new(0.96, 0.5, 0.05)
+    }
+}
+
+impl<T: PixelIndexable> Effect<T> for Ripple<T>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    fn tick(&mut self, _color: &mut Color) {
+        if self.rng.gen_bool(self.rain as f64) {
+            let idx = self.random_index();
+            let depth = self.rng.gen_range(0.5, 1.0);
+            self.splash(idx, depth);
+        }
+        let mut next: GenericArray<f32, T::SIZE> = GenericArray::default();
+        let px_iter: PixelIterator<T> = PixelIterator::all();
+        for idx in px_iter {
+            let i = idx.usize();
+            let h = self.height[i];
+            let velocity = (h - self.prev[i]) * self.damping;
+            let accel = self.tension * (self.neighbours(idx) - h);
+            // Ease back towards still water so drops don't lower the level for good
+            next[i] = (h + velocity + accel) * 0.995;
+        }
+        self.prev = core::mem::replace(&mut self.height, next);
+    }
+
+    fn render(&self, color: Color, model: &mut T) {
+        let white = Color::new(100.0, 0.0, color.hue);
+        for idx in model.iter_pixels() {
+            let h = self.height[idx.usize()];
+            let base = Color {
+                l: color.l * (0.6 + 0.4 * h).clamp(0.0, 1.5),
+                ..color
+            };
+            // Light glints off the steep faces of the waves
+            let glint = smoothstep(0.15, 0.5, self.slope(idx));
+            *model.get_mut(idx) = base.mix(&white, glint * 0.7);
+        }
+    }
+
+    fn rotate_cw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.rain = (self.rain * 1.2).min(0.5);
+        out.show(format_args!("Rain: {}", self.rain));
+    }
+
+    fn rotate_ccw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.rain = (self.rain / 1.2).max(0.001);
+        out.show(format_args!("Rain: {}", self.rain));
+    }
+
+    fn click(&mut self, _color: &mut Color, _out: &mut Readout) {
+        // Drop a stone into the middle of the top
+        if let Some(idx) = T::index_top() {
+            self.splash(idx, 2.0);
+        }
+    }
+}
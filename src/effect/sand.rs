@@ -0,0 +1,189 @@
+use crate::{
+    effect::{particle::Heading, Readout},
+    pixelindex::PixelIterator,
+    Color, Effect, FaceType, PixelIndex, PixelIndexable,
+};
+use core::marker::PhantomData;
+use generic_array::{ArrayLength, GenericArray};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+// Grains poured per detent of the knob
+const POUR: usize = 3;
+
+/// Falling sand: grains tumble off the top face, run down the sides and heap up at the bottom,
+/// sliding off the side of the pile when it gets too steep.
+pub struct Sand<T: PixelIndexable>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    // Hue of the grain in each cell, offset by one so that 0.0 is empty
+    grains: GenericArray<f32, T::SIZE>,
+    rng: SmallRng,
+    _pd: PhantomData<T>,
+}
+
+impl<T: PixelIndexable> Sand<T>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    /// Start with the bottom `depth` rows of the sides full of sand of `hue`
+    pub fn new(depth: f32, hue: f32) -> Self {
+        let mut grains: GenericArray<f32, T::SIZE> = GenericArray::default();
+        let px_iter: PixelIterator<T> = PixelIterator::all();
+        for idx in px_iter {
+            let (_, height) = idx.as_spherical();
+            if let FaceType::Side = idx.face_type() {
+                if height * 7.0 < depth {
+                    grains[idx.usize()] = hue + 1.0;
+                }
+            }
+        }
+        Self {
+            grains,
+            rng: SmallRng::seed_from_u64(1234),
+            _pd: PhantomData,
+        }
+    }
+
+    fn is_empty(&self, idx: PixelIndex<T>) -> bool {
+        self.grains[idx.usize()] <= 0.0
+    }
+
+    /// Drop grains of `hue` onto empty cells of the top face
+    fn pour(&mut self, hue: f32) {
+        for _ in 0..POUR {
+            let dir = self.rng.gen_range(0.0, 1.0);
+            let idx = T::spherical_to_index(dir, self.rng.gen_range(0.75, 1.0));
+            if self.is_empty(idx) {
+                self.grains[idx.usize()] = hue + 1.0;
+            }
+        }
+    }
+
+    /// Turn the sides upside down, leaving the sand at the top to fall again
+    fn flip(&mut self) {
+        let px_iter: PixelIterator<T> = PixelIterator::all();
+        for head in px_iter {
+            // Start from the top of each side column and swap its rows end for end
+            let is_head = match head.up() {
+                Some(up) => matches!(up.face_type(), FaceType::Top),
+                None => true,
+            };
+            if !matches!(head.face_type(), FaceType::Side) || !is_head {
+                continue;
+            }
+            let rows = head.iter_down().count();
+            for k in 0..rows / 2 {
+                let a = head.iter_down().nth(k).unwrap().usize();
+                let b = head.iter_down().nth(rows - 1 - k).unwrap().usize();
+                self.grains.swap(a, b);
+            }
+        }
+    }
+
+    /// Where a grain moves this tick: straight down if it can, otherwise sliding off
+    /// diagonally down one side or the other
+    fn fall(&mut self, idx: PixelIndex<T>) -> Option<PixelIndex<T>> {
+        if let Some(below) = idx.down().filter(|&b| self.is_empty(b)) {
+            return Some(below);
+        }
+        let slides = if self.rng.gen_bool(0.5) {
+            [Heading::DownLeft, Heading::DownRight]
+        } else {
+            [Heading::DownRight, Heading::DownLeft]
+        };
+        slides
+            .iter()
+            .filter_map(|h| h.step(idx))
+            .find(|&n| self.is_empty(n))
+    }
+}
+
+impl<T: PixelIndexable> Default for Sand<T>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    fn default() -> Self {
+        Self::new(2.0, 40.0)
+    }
+}
+
+impl<T: PixelIndexable> Effect<T> for Sand<T>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    fn tick(&mut self, _color: &mut Color) {
+        // Only grains that were already in place at the start of the tick move, so none fall
+        // more than one cell at a time
+        let settled = self.grains.clone();
+        let px_iter: PixelIterator<T> = PixelIterator::all();
+        for idx in px_iter {
+            let i = idx.usize();
+            if settled[i] > 0.0 && self.grains[i] == settled[i] {
+                if let Some(dest) = self.fall(idx) {
+                    self.grains[dest.usize()] = self.grains[i];
+                    self.grains[i] = 0.0;
+                }
+            }
+        }
+    }
+
+    fn render(&self, color: Color, model: &mut T) {
+        for idx in model.iter_pixels() {
+            let grain = self.grains[idx.usize()];
+            *model.get_mut(idx) = if grain > 0.0 {
+                Color::new(color.l, color.chroma, grain - 1.0)
+            } else {
+                Color::new(color.l * 0.05, 0.0, 0.0)
+            };
+        }
+    }
+
+    fn rotate_cw(&mut self, color: &mut Color, _out: &mut Readout) {
+        self.pour(color.hue.to_positive_degrees());
+    }
+
+    fn rotate_ccw(&mut self, color: &mut Color, _out: &mut Readout) {
+        self.pour(color.hue.to_positive_degrees());
+    }
+
+    fn click(&mut self, _color: &mut Color, out: &mut Readout) {
+        out.show(format_args!("Flip"));
+        self.flip();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Lantern;
+
+    fn count(sand: &Sand<Lantern>) -> usize {
+        sand.grains.iter().filter(|&&g| g > 0.0).count()
+    }
+
+    #[test]
+    fn flip_keeps_every_grain() {
+        let mut sand: Sand<Lantern> = Sand::new(2.0, 40.0);
+        let before = sand.grains.clone();
+        assert_eq!(count(&sand), 40);
+        sand.flip();
+        assert_eq!(count(&sand), 40);
+        sand.flip();
+        assert_eq!(sand.grains, before);
+    }
+
+    #[test]
+    fn flip_moves_the_pile_to_the_top() {
+        let mut sand: Sand<Lantern> = Sand::new(2.0, 40.0);
+        sand.flip();
+        let px_iter: PixelIterator<Lantern> = PixelIterator::all();
+        for idx in px_iter {
+            if let FaceType::Side = idx.face_type() {
+                let (_, height) = idx.as_spherical();
+                let full = sand.grains[idx.usize()] > 0.0;
+                assert_eq!(full, height * 7.0 > 2.0, "pixel {}", idx.usize());
+            }
+        }
+    }
+}
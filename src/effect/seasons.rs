@@ -0,0 +1,247 @@
+use crate::{
+    effect::{particle::Particle, Readout, TICKS_PER_SECOND},
+    math::smoothstep,
+    Color, Effect, FaceType, PixelIndexable,
+};
+use core::marker::PhantomData;
+use generic_array::{ArrayLength, GenericArray};
+use num_traits::Float;
+use palette::{Hue, Mix};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+const NUM_PARTICLES: usize = 12;
+// Fraction of each season spent cross-fading into the next
+const FADE: f32 = 0.2;
+// Snow added to a pixel of the top face by each flake that settles there
+const FLAKE_DEPTH: f32 = 0.15;
+
+#[derive(Clone, Copy, Debug)]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+struct Palette {
+    sky: Color,
+    ground: Color,
+    particle: Color,
+    // Random hue variation of each particle, in degrees
+    hue_spread: f32,
+}
+
+impl Season {
+    /// Season and fraction through it at a point in the year, from 0.0 to 4.0
+    pub fn from_year(year: f32) -> (Self, f32) {
+        use Season::*;
+        let season = [Spring, Summer, Autumn, Winter][(year as usize) % 4];
+        (season, year.fract())
+    }
+    pub fn next(self) -> Self {
+        use Season::*;
+        match self {
+            Spring => Summer,
+            Summer => Autumn,
+            Autumn => Winter,
+            Winter => Spring,
+        }
+    }
+    fn palette(self) -> Palette {
+        use Season::*;
+        match self {
+            Spring => Palette {
+                sky: Color::new(55.0, 35.0, 230.0),
+                ground: Color::new(35.0, 50.0, 135.0),
+                particle: Color::new(80.0, 40.0, 350.0),
+                hue_spread: 20.0,
+            },
+            Summer => Palette {
+                sky: Color::new(75.0, 60.0, 85.0),
+                ground: Color::new(40.0, 60.0, 120.0),
+                particle: Color::new(90.0, 70.0, 95.0),
+                hue_spread: 10.0,
+            },
+            Autumn => Palette {
+                sky: Color::new(40.0, 20.0, 260.0),
+                ground: Color::new(25.0, 40.0, 60.0),
+                particle: Color::new(50.0, 80.0, 45.0),
+                hue_spread: 40.0,
+            },
+            Winter => Palette {
+                sky: Color::new(30.0, 15.0, 260.0),
+                ground: Color::new(15.0, 10.0, 250.0),
+                particle: Color::new(95.0, 5.0, 250.0),
+                hue_spread: 0.0,
+            },
+        }
+    }
+    /// Chance per tick of a new particle appearing
+    fn spawn_chance(self) -> f64 {
+        use Season::*;
+        match self {
+            Spring => 0.1,
+            Summer => 0.05,
+            Autumn => 0.15,
+            Winter => 0.3,
+        }
+    }
+}
+
+pub struct Seasons<T: PixelIndexable>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    // Position in the year, from 0.0 (start of spring) to 4.0
+    year: f32,
+    // Length of each season, in seconds
+    season_length: f32,
+    // Depth of snow lying on each pixel of the top face
+    snow: GenericArray<f32, T::SIZE>,
+    particles: [Particle; NUM_PARTICLES],
+    rng: SmallRng,
+    _pd: PhantomData<T>,
+}
+
+impl<T: PixelIndexable> Seasons<T>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    pub fn new(season_length: f32) -> Self {
+        Self {
+            year: 0.0,
+            season_length,
+            snow: GenericArray::default(),
+            particles: [Particle::dead(); NUM_PARTICLES],
+            rng: SmallRng::seed_from_u64(1234),
+            _pd: PhantomData,
+        }
+    }
+
+    pub fn season(&self) -> Season {
+        Season::from_year(self.year).0
+    }
+
+    fn scrub(&mut self, delta: f32, out: &mut Readout) {
+        self.year = (self.year + delta + 4.0) % 4.0;
+        out.show(format_args!("{:?} {}", self.season(), self.year.fract()));
+    }
+
+    fn spawn(&mut self, season: Season) {
+        let palette = season.palette();
+        let rng = &mut self.rng;
+        if let Some(p) = self.particles.iter_mut().find(|p| !p.is_alive()) {
+            let hue = rng.gen_range(-1.0, 1.0) * palette.hue_spread;
+            let color = palette.particle.shift_hue(hue);
+            let dir = rng.gen_range(0.0, 1.0);
+            *p = match season {
+                // Blossoms and leaves drop from the branches at the top of the sides
+                Season::Spring | Season::Autumn => Particle::new(dir, 4.0 / 7.0, 0.0, 0.0, color),
+                // Summer glints flare up in place
+                Season::Summer => Particle::new(dir, rng.gen_range(0.0, 1.0), 0.0, 0.0, color),
+                // Snow falls over the edge of the top face
+                Season::Winter => {
+                    let ddir = rng.gen_range(-0.002, 0.002);
+                    Particle::new(dir, rng.gen_range(0.5, 1.0), ddir, -0.01, color)
+                }
+            };
+        }
+    }
+}
+
+impl<T: PixelIndexable> Default for Seasons<T>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    fn default() -> Self {
+        Self::new(120.0)
+    }
+}
+
+impl<T: PixelIndexable> Effect<T> for Seasons<T>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    fn tick(&mut self, _color: &mut Color) {
+        let dt = 1.0 / TICKS_PER_SECOND as f32;
+        self.year = (self.year + dt / self.season_length) % 4.0;
+        let season = self.season();
+        if self.rng.gen_bool(season.spawn_chance()) {
+            self.spawn(season);
+        }
+        let (snow, rng) = (&mut self.snow, &mut self.rng);
+        for p in self.particles.iter_mut().filter(|p| p.is_alive()) {
+            match season {
+                Season::Spring => {
+                    // Petals drift gently as they fall
+                    p.ddir = 0.005 * (p.height * 30.0).sin();
+                    p.tick(0.0, 0.0);
+                    p.height -= 0.005;
+                }
+                Season::Autumn => {
+                    // Leaves swing side to side and tumble faster than petals
+                    p.ddir = 0.01 * (p.height * 20.0).sin();
+                    p.tick(0.0, 0.0);
+                    p.height -= 0.01;
+                }
+                Season::Summer => {
+                    p.life -= 0.1;
+                    p.color.l = 90.0 * p.life;
+                }
+                Season::Winter => {
+                    p.tick(0.0, 0.0);
+                    // Flakes over the top face settle and build up the snow, the rest fall
+                    // past the edge
+                    let idx = p.index::<T>();
+                    if let FaceType::Top = idx.face_type() {
+                        if rng.gen_ratio(1, 10) {
+                            let depth = &mut snow[idx.usize()];
+                            *depth = (*depth + FLAKE_DEPTH).min(1.0);
+                            p.kill();
+                        }
+                    }
+                }
+            }
+            if p.height < 0.0 {
+                p.kill();
+            }
+        }
+        // Snow thaws slowly through spring, and quicker if the knob skips the year past it
+        let melt = match season {
+            Season::Winter => 0.0,
+            Season::Spring => 0.002,
+            Season::Summer | Season::Autumn => 0.01,
+        };
+        for depth in self.snow.iter_mut() {
+            *depth = (*depth - melt).max(0.0);
+        }
+    }
+
+    fn render(&self, _color: Color, model: &mut T) {
+        let (season, frac) = Season::from_year(self.year);
+        let (current, next) = (season.palette(), season.next().palette());
+        let blend = smoothstep(1.0 - FADE, 1.0, frac);
+        let sky = current.sky.mix(&next.sky, blend);
+        let ground = current.ground.mix(&next.ground, blend);
+        let white = Color::new(95.0, 0.0, 250.0);
+        for idx in model.iter_pixels() {
+            let (_, height) = idx.as_spherical();
+            let px = match idx.face_type() {
+                FaceType::Top => sky.mix(&white, self.snow[idx.usize()]),
+                FaceType::Side => ground.mix(&sky, height),
+            };
+            *model.get_mut(idx) = px;
+        }
+        for p in &self.particles {
+            p.render(model);
+        }
+    }
+
+    fn rotate_cw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.scrub(0.05, out);
+    }
+
+    fn rotate_ccw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.scrub(-0.05, out);
+    }
+}
@@ -1,94 +1,131 @@
-use crate::{Color, Effect, PixelIndexable};
-use core::marker::PhantomData;
+use crate::{
+    effect::{particle::Particle, Readout},
+    Color, Effect, PixelIndexable,
+};
+use core::{f32::consts::PI, marker::PhantomData};
+use num_traits::Float;
 use palette::{Hue, Mix};
 use rand::{rngs::SmallRng, Rng, SeedableRng};
 
-#[derive(Copy, Clone, Debug)]
-struct Particle {
-    x: f32,
-    y: f32,
-    dx: f32,
-    dy: f32,
-    color: Color,
-}
-
-impl Particle {
-    fn new(x: f32, y: f32, dx: f32, dy: f32, color: Color) -> Self {
-        Self {
-            x,
-            y,
-            dx,
-            dy,
-            color,
-        }
-    }
-    fn tick(&mut self, g: f32) {
-        self.x = (self.x + self.dx + 1.0) % 1.0;
-        self.y += self.dy;
-        self.dy -= g;
-    }
-    fn shuffle(&mut self, rng: &mut SmallRng) {
-        self.y = 0.0;
-        self.dy = rng.gen_range(0.03, 0.04);
-        self.dx = rng.gen_range(-0.01, 0.01);
-        self.x = rng.gen_range(0.0, 1.0);
-        self.color = Color::new(100.0, 50.0, rng.gen_range(0.0, 360.0));
-    }
-}
-
-const NUM_SPARKS: usize = 2;
+const NUM_ROCKETS: usize = 3;
+const NUM_SPARKS: usize = 32;
+const BURST_SIZE: usize = 10;
+const GRAVITY: f32 = 0.002;
+const DRAG: f32 = 0.08;
+// Life lost by each burst particle per tick
+const BURN: f32 = 0.04;
 
 pub struct Sparks<T: PixelIndexable> {
     _pd: PhantomData<T>,
     fade: f32,
     shift: f32,
+    // Chance per tick of launching a rocket
+    launch_rate: f32,
+    rockets: [Particle; NUM_ROCKETS],
     sparks: [Particle; NUM_SPARKS],
     rng: SmallRng,
 }
 
 impl<T: PixelIndexable> Sparks<T> {
-    pub fn new(fade: f32, shift: f32) -> Self {
-        let _pd = PhantomData;
-        let mut rng = SmallRng::seed_from_u64(137);
-        let mut sparks = [Particle::new(
-            0.0,
-            0.0,
-            0.0,
-            0.0,
-            Color::new(10.0, 10.0, rng.gen_range(0.0, 360.0)),
-        ); NUM_SPARKS];
-        for p in sparks.iter_mut() {
-            p.shuffle(&mut rng);
-        }
+    pub fn new(fade: f32, shift: f32, launch_rate: f32) -> Self {
         Self {
-            _pd,
+            _pd: PhantomData,
             fade,
             shift,
-            sparks,
-            rng,
+            launch_rate,
+            rockets: [Particle::dead(); NUM_ROCKETS],
+            sparks: [Particle::dead(); NUM_SPARKS],
+            rng: SmallRng::seed_from_u64(137),
+        }
+    }
+
+    fn launch(&mut self) {
+        let rng = &mut self.rng;
+        if let Some(r) = self.rockets.iter_mut().find(|r| !r.is_alive()) {
+            let color = Color::new(60.0, 20.0, rng.gen_range(30.0, 60.0));
+            *r = Particle::new(
+                rng.gen_range(0.0, 1.0),
+                0.0,
+                rng.gen_range(-0.003, 0.003),
+                rng.gen_range(0.045, 0.06),
+                color,
+            );
+        }
+    }
+
+    /// Spray particles out in a ring from where a rocket ran out of lift
+    fn burst(&mut self, dir: f32, height: f32) {
+        let rng = &mut self.rng;
+        let color = Color::new(100.0, 80.0, rng.gen_range(0.0, 360.0));
+        let speed = rng.gen_range(0.02, 0.035);
+        let offset = rng.gen_range(0.0, 2.0 * PI);
+        let free = self.sparks.iter_mut().filter(|p| !p.is_alive());
+        for (i, p) in free.take(BURST_SIZE).enumerate() {
+            let angle = offset + 2.0 * PI * i as f32 / BURST_SIZE as f32;
+            // Around the sides a full turn of `dir` is four faces wide, so spread it less
+            let ddir = 0.5 * speed * angle.cos();
+            let dheight = speed * angle.sin();
+            *p = Particle::new(
+                dir,
+                height,
+                ddir,
+                dheight,
+                color.shift_hue(rng.gen_range(-20.0, 20.0)),
+            );
         }
     }
-    pub fn default() -> Self {
-        Self::new(0.2, 0.0)
+}
+
+impl<T: PixelIndexable> Default for Sparks<T> {
+    fn default() -> Self {
+        Self::new(0.3, 0.0, 0.05)
     }
 }
 
 impl<T: PixelIndexable> Effect<T> for Sparks<T> {
     fn render(&self, _color: Color, model: &mut T) {
+        // Fading the previous frame rather than clearing it leaves a trail behind each particle
         model.map_pixels(|_idx, px| {
             px.mix(&Color::new(0.0, 0.0, px.hue), self.fade)
                 .shift_hue(self.shift)
         });
-        for p in &self.sparks {
-            *model.get_spherical_mut(p.x, p.y) = p.color;
+        for p in self.rockets.iter().chain(self.sparks.iter()) {
+            p.render(model);
         }
     }
     fn tick(&mut self, _color: &mut Color) {
-        for p in self.sparks.iter_mut() {
-            p.tick(0.001);
-            if p.dy <= 0.0 {
-                p.shuffle(&mut self.rng);
+        if self.rng.gen_bool(self.launch_rate as f64) {
+            self.launch();
+        }
+        let mut bursts = [None; NUM_ROCKETS];
+        for (r, burst) in self.rockets.iter_mut().zip(bursts.iter_mut()) {
+            if r.is_alive() {
+                r.tick(GRAVITY, 0.0);
+                // Rockets burst at the top of their climb, or when they reach the top face
+                if r.dheight <= 0.01 || r.height >= 0.9 {
+                    *burst = Some((r.dir, r.height.min(0.9)));
+                    r.kill();
+                }
+            }
+        }
+        for &(dir, height) in bursts.iter().flatten() {
+            self.burst(dir, height);
+        }
+        for p in self.sparks.iter_mut().filter(|p| p.is_alive()) {
+            p.tick(GRAVITY, DRAG);
+            p.life -= BURN;
+            p.color.l = 100.0 * p.life.max(0.0);
+            if !p.in_bounds() {
+                p.kill();
             }
         }
     }
+    fn rotate_cw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.launch_rate = (self.launch_rate * 1.2).min(0.5);
+        out.show(format_args!("Launch rate: {}", self.launch_rate));
+    }
+    fn rotate_ccw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.launch_rate = (self.launch_rate / 1.2).max(0.005);
+        out.show(format_args!("Launch rate: {}", self.launch_rate));
+    }
 }
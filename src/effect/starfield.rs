@@ -0,0 +1,225 @@
+use crate::{
+    blackbody,
+    effect::{
+        particle::{Crawler, Heading},
+        Readout,
+    },
+    pixelindex::PixelIterator,
+    Color, Effect, FaceType, PixelIndexable,
+};
+use core::{f32::consts::PI, marker::PhantomData};
+use generic_array::{ArrayLength, GenericArray};
+use num_traits::Float;
+use palette::Mix;
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+// Fraction of a shooting star's trail kept each tick
+const TRAIL_DECAY: f32 = 0.6;
+
+/// Night sky of twinkling stars, each with its own colour temperature, and the odd shooting
+/// star streaking around the sides.
+pub struct Starfield<T: PixelIndexable>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    // Brightness of the star at each pixel, 0.0 where there is none
+    stars: GenericArray<f32, T::SIZE>,
+    // Twinkle phase of each star, in radians
+    phase: GenericArray<f32, T::SIZE>,
+    // Colour temperature of each star, in kelvin
+    kelvin: GenericArray<f32, T::SIZE>,
+    // Brightness of shooting star trails
+    trail: GenericArray<f32, T::SIZE>,
+    // Fraction of pixels holding a star
+    density: f32,
+    shooting: Option<Crawler<T>>,
+    rng: SmallRng,
+    _pd: PhantomData<T>,
+}
+
+impl<T: PixelIndexable> Starfield<T>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    pub fn new(density: f32) -> Self {
+        let mut rv = Self {
+            stars: GenericArray::default(),
+            phase: GenericArray::default(),
+            kelvin: GenericArray::default(),
+            trail: GenericArray::default(),
+            density,
+            shooting: None,
+            rng: SmallRng::seed_from_u64(1234),
+            _pd: PhantomData,
+        };
+        rv.scatter();
+        rv
+    }
+
+    fn scatter(&mut self) {
+        let rng = &mut self.rng;
+        for ((star, phase), kelvin) in self
+            .stars
+            .iter_mut()
+            .zip(self.phase.iter_mut())
+            .zip(self.kelvin.iter_mut())
+        {
+            *star = if rng.gen_bool(self.density as f64) {
+                rng.gen_range(0.2, 1.0)
+            } else {
+                0.0
+            };
+            *phase = rng.gen_range(0.0, 2.0 * PI);
+            *kelvin = rng.gen_range(3000.0, 12000.0);
+        }
+    }
+
+    fn launch(&mut self) {
+        let rng = &mut self.rng;
+        let heading = [
+            Heading::Left,
+            Heading::Right,
+            Heading::DownLeft,
+            Heading::DownRight,
+        ][rng.gen_range(0, 4)];
+        // Start high on the sides so diagonal streaks have room to fall
+        let idx = T::cylindrical_to_index(rng.gen_range(0.0, 1.0), rng.gen_range(0.6, 1.0));
+        let steps = rng.gen_range(6, 16);
+        self.shooting = Some(Crawler::new(
+            idx,
+            heading,
+            steps,
+            Color::new(100.0, 0.0, 0.0),
+        ));
+    }
+
+    fn adjust(&mut self, factor: f32, out: &mut Readout) {
+        self.density = (self.density * factor).clamp(0.02, 0.8);
+        out.show(format_args!("Density: {}", self.density));
+        self.scatter();
+    }
+}
+
+impl<T: PixelIndexable> Default for Starfield<T>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    fn default() -> Self {
+        Self::new(0.2)
+    }
+}
+
+impl<T: PixelIndexable> Effect<T> for Starfield<T>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    fn tick(&mut self, _color: &mut Color) {
+        for phase in self.phase.iter_mut() {
+            *phase = (*phase + 0.3) % (2.0 * PI);
+        }
+        for level in self.trail.iter_mut() {
+            *level *= TRAIL_DECAY;
+        }
+        if self.shooting.is_none() && self.rng.gen_ratio(1, 80) {
+            self.launch();
+        }
+        if let Some(star) = &mut self.shooting {
+            self.trail[star.idx.usize()] = 1.0;
+            if !star.step() {
+                self.shooting = None;
+            }
+        }
+    }
+
+    fn render(&self, _color: Color, model: &mut T) {
+        let sky = Color::new(2.0, 10.0, 260.0);
+        let px_iter: PixelIterator<T> = PixelIterator::all();
+        for idx in px_iter {
+            let i = idx.usize();
+            let mut px = sky;
+            if self.stars[i] > 0.0 {
+                let twinkle = 0.6 + 0.4 * self.phase[i].sin();
+                let star = blackbody(self.kelvin[i]);
+                px = Color {
+                    l: star.l * self.stars[i] * twinkle,
+                    ..star
+                };
+            }
+            // Stars sit lower in the sky on the sides, dimmed by the horizon
+            if let FaceType::Side = idx.face_type() {
+                let (_, height) = idx.as_spherical();
+                px.l *= 0.5 + height;
+            }
+            *model.get_mut(idx) = px.mix(&Color::new(100.0, 0.0, 0.0), self.trail[i]);
+        }
+    }
+
+    fn rotate_cw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.adjust(1.2, out);
+    }
+
+    fn rotate_ccw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.adjust(1.0 / 1.2, out);
+    }
+}
+
+/// Random flashes of the current colour, laid over whatever effect is running
+pub struct Glitter<T: PixelIndexable>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    // Brightness of the flash at each pixel
+    flash: GenericArray<f32, T::SIZE>,
+    // Chance per tick of each pixel flashing
+    rate: f32,
+    // Fraction of flash brightness kept each tick
+    decay: f32,
+    rng: SmallRng,
+    _pd: PhantomData<T>,
+}
+
+impl<T: PixelIndexable> Glitter<T>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    pub fn new(rate: f32, decay: f32) -> Self {
+        Self {
+            flash: GenericArray::default(),
+            rate,
+            decay,
+            rng: SmallRng::seed_from_u64(4321),
+            _pd: PhantomData,
+        }
+    }
+}
+
+impl<T: PixelIndexable> Default for Glitter<T>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    fn default() -> Self {
+        Self::new(0.02, 0.5)
+    }
+}
+
+impl<T: PixelIndexable> Effect<T> for Glitter<T>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    fn tick(&mut self, _color: &mut Color) {
+        let rng = &mut self.rng;
+        for flash in self.flash.iter_mut() {
+            *flash *= self.decay;
+            if rng.gen_bool(self.rate as f64) {
+                *flash = 1.0;
+            }
+        }
+    }
+
+    // Mixes flashes into the frame as it stands, so composite this over a copy of the rendered
+    // effect rather than the model itself
+    fn render(&self, color: Color, model: &mut T) {
+        let bright = Color { l: 100.0, ..color };
+        model.map_pixels(|idx, px| px.mix(&bright, self.flash[idx.usize()]));
+    }
+}
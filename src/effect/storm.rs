@@ -1,10 +1,33 @@
-use crate::{math::noise::Simplex, Color, Effect, PixelIndexable};
-use core::marker::PhantomData;
-use palette::{Mix, Shade};
+use crate::{
+    effect::Readout,
+    math::{noise::Simplex, wrap},
+    Color, Effect, PixelIndex, PixelIndexable,
+};
+use core::{f32::consts::PI, marker::PhantomData};
+use generic_array::{ArrayLength, GenericArray};
+use num_traits::Float;
+use palette::Mix;
 use rand::{rngs::SmallRng, Rng, SeedableRng};
 
-const NUM_DROPS: usize = 8;
-pub struct Storm<T: PixelIndexable> {
+const NUM_DROPS: usize = 16;
+// Maximum number of channels in one bolt, including the main channel
+const MAX_FORKS: usize = 4;
+// Upper bound on the length of a single channel, in pixels
+const MAX_BOLT_STEPS: usize = 24;
+const MAX_STROKES: u8 = 4;
+
+#[derive(Copy, Clone, Debug)]
+struct Raindrop {
+    dir: f32,
+    height: f32,
+    fall: f32,
+    drift: f32,
+}
+
+pub struct Storm<T: PixelIndexable>
+where
+    T::SIZE: ArrayLength<f32>,
+{
     _pd: PhantomData<T>,
     bg_color: Color,
     drop_color: Color,
@@ -12,33 +35,60 @@ pub struct Storm<T: PixelIndexable> {
     cloud_speed: f32,
     drop_speed: f32,
     offset: f32,
+    // Rotation of the clouds in turns, carried along by the wind
+    drift: f32,
+    wind: f32,
+    wind_target: f32,
+    wind_max: f32,
+    gust_duration: u32,
+    // Scales drop count, wind strength and strike chance
+    intensity: f32,
     strike_chance: f32,
-    bolt: (f32, f32),
+    fork_chance: f32,
+    // Per-pixel brightness of the current bolt's channels
+    bolt: GenericArray<f32, T::SIZE>,
+    bolt_level: f32,
     bolt_fade: f32,
+    // Return strokes remaining in the current strike
+    strokes: u8,
+    flash: f32,
     noise: Simplex,
-    drops: [(f32, f32, f32); NUM_DROPS],
+    drops: [Raindrop; NUM_DROPS],
     rng: SmallRng,
 }
 
-impl<T: PixelIndexable> Storm<T> {
+impl<T: PixelIndexable> Storm<T>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         bg_color: Color,
         drop_color: Color,
         drop_fade: f32,
         cloud_speed: f32,
         drop_speed: f32,
+        wind_max: f32,
         strike_chance: f32,
+        fork_chance: f32,
         bolt_fade: f32,
+        flash: f32,
     ) -> Self {
         let mut rng = SmallRng::seed_from_u64(1234);
         let noise = Simplex::new(137);
         let _pd = PhantomData;
         let offset = 0.0;
-        let mut drops = [(0.0, 0.0, 0.0); NUM_DROPS];
+        let mut drops = [Raindrop {
+            dir: 0.0,
+            height: 0.0,
+            fall: 0.0,
+            drift: 0.0,
+        }; NUM_DROPS];
         for drop in drops.iter_mut() {
-            *drop = (rng.gen_range(0.0, 1.0), rng.gen_range(0.0, 1.0), 0.0);
+            drop.dir = rng.gen_range(0.0, 1.0);
+            drop.height = rng.gen_range(0.0, 1.0);
         }
-        let bolt = (0.0, 0.0);
+        let bolt = GenericArray::default();
         Self {
             _pd,
             bg_color,
@@ -47,10 +97,20 @@ impl<T: PixelIndexable> Storm<T> {
             drop_speed,
             drop_fade,
             offset,
+            drift: 0.0,
+            wind: 0.0,
+            wind_target: 0.0,
+            wind_max,
+            gust_duration: 50,
+            intensity: 0.5,
             noise,
             bolt,
+            bolt_level: 0.0,
             strike_chance,
+            fork_chance,
             bolt_fade,
+            strokes: 0,
+            flash,
             drops,
             rng,
         }
@@ -59,11 +119,80 @@ impl<T: PixelIndexable> Storm<T> {
     pub fn default() -> Self {
         let dim = Color::new(5.0, 5.0, 305.0);
         let drop = Color::new(0.0, 0.0, 305.0);
-        Storm::new(dim, drop, 0.01, 0.05, 0.02, 0.15, 0.8)
+        Storm::new(dim, drop, 0.01, 0.05, 0.02, 0.02, 0.1, 0.15, 0.7, 0.6)
+    }
+
+    fn active_drops(&self) -> usize {
+        ((NUM_DROPS as f32 * self.intensity).ceil() as usize).max(1)
+    }
+
+    /// Local wind speed, varying smoothly around the sides so that it wraps seamlessly.
+    fn wind_at(&self, dir: f32) -> f32 {
+        let (x, y) = ((dir * 2.0 * PI).cos(), (dir * 2.0 * PI).sin());
+        let gust = self.noise.noise_3d(x, y, self.offset);
+        self.wind * (1.0 + 0.5 * gust)
+    }
+
+    /// Trace a new bolt from a random point on the top face down to the ground.
+    fn strike(&mut self) {
+        for px in self.bolt.iter_mut() {
+            *px = 0.0;
+        }
+        let start = T::spherical_to_index(
+            self.rng.gen_range(0.0, 1.0),
+            self.rng.gen_range(5.0 / 8.0, 1.0),
+        );
+        let mut forks: [Option<(PixelIndex<T>, f32)>; MAX_FORKS] = [None; MAX_FORKS];
+        forks[0] = Some((start, 1.0));
+        let mut num_forks = 1;
+        let mut fork = 0;
+        while fork < num_forks {
+            if let Some((mut idx, level)) = forks[fork] {
+                for _ in 0..MAX_BOLT_STEPS {
+                    self.light(idx, level);
+                    if num_forks < MAX_FORKS && self.rng.gen_bool(self.fork_chance as f64) {
+                        if let Some(side) = self.sideways(idx) {
+                            forks[num_forks] = Some((side, level * 0.5));
+                            num_forks += 1;
+                        }
+                    }
+                    // Channels wander sideways as they descend
+                    if self.rng.gen_ratio(1, 3) {
+                        if let Some(side) = self.sideways(idx) {
+                            idx = side;
+                            self.light(idx, level);
+                        }
+                    }
+                    match idx.down() {
+                        Some(below) => idx = below,
+                        None => break,
+                    }
+                }
+            }
+            fork += 1;
+        }
+        self.bolt_level = 1.0;
+        self.strokes = self.rng.gen_range(0, MAX_STROKES);
+    }
+
+    fn sideways(&mut self, idx: PixelIndex<T>) -> Option<PixelIndex<T>> {
+        if self.rng.gen_bool(0.5) {
+            idx.left()
+        } else {
+            idx.right()
+        }
+    }
+
+    fn light(&mut self, idx: PixelIndex<T>, level: f32) {
+        let px = &mut self.bolt[idx.usize()];
+        *px = px.max(level);
     }
 }
 
-impl<T: PixelIndexable> Effect<T> for Storm<T> {
+impl<T: PixelIndexable> Effect<T> for Storm<T>
+where
+    T::SIZE: ArrayLength<f32>,
+{
     fn render(&self, _color: Color, model: &mut T) {
         for idx in model.iter_pixels() {
             match idx.face_type() {
@@ -73,48 +202,82 @@ impl<T: PixelIndexable> Effect<T> for Storm<T> {
                 }
                 crate::FaceType::Top => {
                     let (dir, height) = idx.as_spherical();
-                    let x = dir * 256.0;
-                    let y = height * 2.0 + self.offset;
-                    let val = self.noise.noise_2d(x, y);
-                    let l = (val + 1.0) * 25.0;
+                    // Sample round a circle so the clouds wrap seamlessly and turn with the wind
+                    let angle = (dir + self.drift) * 2.0 * PI;
+                    let (x, y) = (2.0 * angle.cos(), 2.0 * angle.sin());
+                    let val = self.noise.noise_3d(x, y, height * 2.0 + self.offset);
+                    // Lightning lights up the clouds from within
+                    let l = (val + 1.0) * 25.0 * (1.0 + self.flash * self.bolt_level);
                     let color = Color::new(l, l, self.bg_color.hue);
                     *model.get_mut(idx) = color;
                 }
             }
         }
-        for &(dir, height, _speed) in &self.drops {
-            *model.get_cylindrical_mut(dir, height) = self.drop_color;
+        for drop in &self.drops[..self.active_drops()] {
+            *model.get_cylindrical_mut(drop.dir, drop.height) = self.drop_color;
         }
-        if self.bolt.1 > 0.01 {
-            for px in model.column_iter_mut(self.bolt.0) {
-                *model.get_mut(px) = self
-                    .bg_color
-                    .mix(&Color::new(100.0, 0.0, self.bg_color.hue), self.bolt.1);
+        if self.bolt_level > 0.01 {
+            let white = Color::new(100.0, 0.0, self.bg_color.hue);
+            for idx in model.iter_pixels() {
+                let level = self.bolt[idx.usize()] * self.bolt_level;
+                if level > 0.01 {
+                    let px = model.get_mut(idx);
+                    *px = px.mix(&white, level);
+                }
             }
         }
     }
     fn tick(&mut self, _color: &mut Color) {
         self.offset += self.cloud_speed;
-        for drop in self.drops.iter_mut() {
-            if drop.1 < 0.0 {
-                *drop = (self.rng.gen_range(0.0, 1.0), 1.0, 0.0);
+        if self.rng.gen_ratio(1, self.gust_duration) {
+            let max = self.wind_max * self.intensity;
+            // A calm storm has no wind, and gen_range panics on an empty range
+            self.wind_target = if max > 0.0 {
+                self.rng.gen_range(-max, max)
+            } else {
+                0.0
+            };
+        }
+        self.wind += (self.wind_target - self.wind) * 0.1;
+        self.drift = wrap(self.drift + self.wind);
+        for i in 0..self.active_drops() {
+            let wind = self.wind_at(self.drops[i].dir);
+            let drop = &mut self.drops[i];
+            if drop.height < 0.0 {
+                drop.dir = self.rng.gen_range(0.0, 1.0);
+                drop.height = 1.0;
+                drop.fall = 0.0;
+                drop.drift = wind;
             } else {
-                drop.1 -= drop.2;
-                drop.2 += self.drop_speed;
+                drop.height -= drop.fall;
+                drop.fall += self.drop_speed;
+                drop.drift += (wind - drop.drift) * 0.3;
+                drop.dir = wrap(drop.dir + drop.drift);
             }
         }
-        if self.rng.gen_bool(self.strike_chance as f64) {
-            self.bolt = (self.rng.gen_range(0.0, 1.0), 1.0);
+        if self.bolt_level > 0.01 {
+            if self.strokes > 0 && self.bolt_level < 0.2 && self.rng.gen_ratio(1, 2) {
+                // Return stroke down the same channel
+                self.strokes -= 1;
+                self.bolt_level = self.rng.gen_range(0.5, 1.0);
+            } else {
+                self.bolt_level *= self.bolt_fade;
+            }
+        } else if self
+            .rng
+            .gen_bool((self.strike_chance * self.intensity) as f64)
+        {
+            self.strike();
         } else {
-            self.bolt.1 *= self.bolt_fade;
+            self.bolt_level = 0.0;
         }
     }
-    fn rotate_cw(&mut self, _color: &mut Color) {
-        //self.bg_color.chroma = 50.0;
-        self.bg_color = self.bg_color.lighten(0.1);
+    fn rotate_cw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.intensity = (self.intensity + 0.1).min(1.0);
+        out.show(format_args!("Intensity: {}", self.intensity));
     }
-    fn rotate_ccw(&mut self, _color: &mut Color) {
-        //self.bg_color.chroma = 0.0;
-        self.bg_color = self.bg_color.darken(0.1);
+    fn rotate_ccw(&mut self, _color: &mut Color, out: &mut Readout) {
+        self.intensity = (self.intensity - 0.1).max(0.0);
+        out.show(format_args!("Intensity: {}", self.intensity));
     }
 }
@@ -0,0 +1,182 @@
+use crate::{effect::TICKS_PER_SECOND, font, Color, Effect, PixelIndexable};
+use core::{
+    fmt::{self, Write},
+    marker::PhantomData,
+};
+use palette::Mix;
+
+const MAX_LEN: usize = 32;
+// Text is laid out on this many columns around the sides
+const COLUMNS: usize = 20;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Mode {
+    /// Scroll the message past once and then disappear
+    Once,
+    /// Keep scrolling the message round
+    Loop,
+}
+
+/// A short ASCII message, such as a knob readout, kept without allocating
+#[derive(Clone, Copy)]
+pub struct Readout {
+    message: [u8; MAX_LEN],
+    len: usize,
+}
+
+impl Readout {
+    pub fn new() -> Self {
+        Self {
+            message: [0; MAX_LEN],
+            len: 0,
+        }
+    }
+
+    /// Replace the message, cutting it short if it doesn't fit
+    pub fn show(&mut self, args: fmt::Arguments) {
+        self.len = 0;
+        // Running out of room only truncates the message
+        let _ = self.write_fmt(args);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn as_str(&self) -> &str {
+        // Only ASCII is ever written
+        core::str::from_utf8(&self.message[..self.len]).unwrap_or("")
+    }
+}
+
+impl Default for Readout {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Write for Readout {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            if self.len == MAX_LEN {
+                return Err(fmt::Error);
+            }
+            // Anything outside ASCII shows up as the font's fallback glyph
+            self.message[self.len] = if c.is_ascii() { c as u8 } else { b'?' };
+            self.len += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Text scrolling around the sides, drawn over whatever is underneath.  It dims and overwrites
+/// the pixels it covers, so draw it onto a copy of the frame rather than an effect's model.
+pub struct Text<T: PixelIndexable> {
+    message: Readout,
+    // Width of the message in columns, including the gaps between glyphs
+    width: usize,
+    // Columns scrolled so far
+    offset: f32,
+    // Columns per second
+    pub speed: f32,
+    // Color of the text, or the current color if none
+    pub color: Option<Color>,
+    mode: Mode,
+    active: bool,
+    _pd: PhantomData<T>,
+}
+
+impl<T: PixelIndexable> Text<T> {
+    pub fn new(speed: f32, color: Option<Color>) -> Self {
+        Self {
+            message: Readout::new(),
+            width: 0,
+            offset: 0.0,
+            speed,
+            color,
+            mode: Mode::Once,
+            active: false,
+            _pd: PhantomData,
+        }
+    }
+
+    /// Start scrolling a new message, cut short if it doesn't fit
+    pub fn show(&mut self, args: fmt::Arguments, mode: Mode) {
+        self.message.show(args);
+        self.width = self.chars().map(|c| font::glyph(c).len() + 1).sum();
+        self.offset = 0.0;
+        self.mode = mode;
+        self.active = true;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.message.as_str().chars()
+    }
+
+    /// Glyph column `k` of the message, or `None` in the gaps and off either end
+    fn column(&self, k: usize) -> Option<u8> {
+        let mut start = 0;
+        for c in self.chars() {
+            let glyph = font::glyph(c);
+            if k < start + glyph.len() {
+                return Some(glyph[k - start]);
+            }
+            start += glyph.len() + 1;
+        }
+        None
+    }
+}
+
+impl<T: PixelIndexable> Default for Text<T> {
+    fn default() -> Self {
+        Self::new(8.0, None)
+    }
+}
+
+impl<T: PixelIndexable> Effect<T> for Text<T> {
+    fn tick(&mut self, _color: &mut Color) {
+        if !self.active {
+            return;
+        }
+        self.offset += self.speed / TICKS_PER_SECOND as f32;
+        // The message enters from one side and is gone once its end has crossed the lantern
+        let span = (self.width + COLUMNS) as f32;
+        if self.offset >= span {
+            match self.mode {
+                Mode::Once => self.active = false,
+                Mode::Loop => self.offset -= span,
+            }
+        }
+    }
+
+    fn render(&self, color: Color, model: &mut T) {
+        if !self.active {
+            return;
+        }
+        let ink = self.color.unwrap_or(color);
+        let black = Color::new(0.0, 0.0, ink.hue);
+        let scrolled = self.offset as usize;
+        for x in 0..COLUMNS {
+            let dir = (x as f32 + 0.5) / COLUMNS as f32;
+            // Message column under this one, counting from the first column to scroll in
+            let col = (x + scrolled)
+                .checked_sub(COLUMNS)
+                .and_then(|k| self.column(k))
+                .unwrap_or(0);
+            for row in 0..font::HEIGHT {
+                let height = (font::HEIGHT - 1 - row) as f32 / font::HEIGHT as f32 + 0.1;
+                let px = model.get_mut(T::cylindrical_to_index(dir, height));
+                // Dim the effect behind the text so it stands out
+                *px = if font::lit(col, row) {
+                    ink
+                } else {
+                    px.mix(&black, 0.7)
+                };
+            }
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use crate::{
+    effect::Readout, math::noise::Simplex, pixelindex::PixelIterator, Color, Effect, FaceType,
+    PixelIndexable,
+};
+use core::marker::PhantomData;
+use generic_array::{ArrayLength, GenericArray};
+use palette::{Hue, Mix};
+
+#[derive(Clone, Copy)]
+pub enum Control {
+    Flow,
+    Hue,
+}
+
+impl Control {
+    pub fn next(self) -> Self {
+        use Control::*;
+        match self {
+            Flow => Hue,
+            Hue => Flow,
+        }
+    }
+}
+
+pub struct Waterfall<T: PixelIndexable>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    water: GenericArray<f32, T::SIZE>,
+    // Water added to each top face pixel per tick
+    rate: f32,
+    // Fraction of each pixel's water that runs downhill per tick
+    flow: f32,
+    foam: f32,
+    offset: f32,
+    control: Control,
+    noise: Simplex,
+    _pd: PhantomData<T>,
+}
+
+impl<T: PixelIndexable> Waterfall<T>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    pub fn new(rate: f32, flow: f32, foam: f32) -> Self {
+        Self {
+            water: GenericArray::default(),
+            rate,
+            flow,
+            foam,
+            offset: 0.0,
+            control: Control::Flow,
+            noise: Simplex::new(137),
+            _pd: PhantomData,
+        }
+    }
+}
+
+impl<T: PixelIndexable> Default for Waterfall<T>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    fn default() -> Self {
+        Self::new(0.05, 0.5, 0.4)
+    }
+}
+
+impl<T: PixelIndexable> Effect<T> for Waterfall<T>
+where
+    T::SIZE: ArrayLength<f32>,
+{
+    fn tick(&mut self, _color: &mut Color) {
+        // Foam travels down the falls faster when more water is flowing
+        self.offset += self.rate * 4.0;
+        let mut next: GenericArray<f32, T::SIZE> = GenericArray::default();
+        let px_iter: PixelIterator<T> = PixelIterator::all();
+        for idx in px_iter {
+            let mut water = self.water[idx.usize()];
+            if let FaceType::Top = idx.face_type() {
+                let (dir, height) = idx.as_spherical();
+                let spring = self.noise.noise_2d(dir * 8.0, height * 8.0 + self.offset);
+                water += self.rate * (spring + 1.0);
+            }
+            let moved = water * self.flow;
+            match idx.down() {
+                Some(below) => {
+                    next[idx.usize()] += water - moved;
+                    next[below.usize()] += moved;
+                }
+                // The pool at the bottom drains away
+                None => next[idx.usize()] += water - moved,
+            }
+        }
+        self.water = next;
+    }
+
+    fn render(&self, color: Color, model: &mut T) {
+        let white = Color::new(100.0, 0.0, color.hue);
+        for idx in model.iter_pixels() {
+            let (dir, height) = idx.as_spherical();
+            let water = self.water[idx.usize()].min(1.0);
+            let mut px = Color {
+                l: color.l * (0.2 + water).min(1.0),
+                ..color
+            };
+            if idx.down().is_none() {
+                // Pool shimmer
+                let shimmer = self.noise.noise_2d(dir * 20.0, self.offset * 0.5);
+                px.l *= 0.8 + 0.3 * shimmer;
+            }
+            let foam = self
+                .noise
+                .noise_3d(dir * 20.0, height * 8.0 + self.offset, self.offset * 0.2);
+            if foam > 1.0 - self.foam {
+                let whitecap = (foam - (1.0 - self.foam)) / self.foam * water;
+                px = px.mix(&white, whitecap);
+            }
+            *model.get_mut(idx) = px;
+        }
+    }
+
+    fn rotate_cw(&mut self, color: &mut Color, out: &mut Readout) {
+        match self.control {
+            Control::Flow => {
+                self.rate = (self.rate * 1.1).min(0.5);
+                out.show(format_args!("Flow: {}", self.rate));
+            }
+            Control::Hue => {
+                *color = color.shift_hue(5.0);
+                out.show(format_args!("Hue: {}", color.hue.to_positive_degrees()));
+            }
+        }
+    }
+
+    fn rotate_ccw(&mut self, color: &mut Color, out: &mut Readout) {
+        match self.control {
+            Control::Flow => {
+                self.rate = (self.rate * 0.9).max(0.005);
+                out.show(format_args!("Flow: {}", self.rate));
+            }
+            Control::Hue => {
+                *color = color.shift_hue(-5.0);
+                out.show(format_args!("Hue: {}", color.hue.to_positive_degrees()));
+            }
+        }
+    }
+
+    fn click(&mut self, _color: &mut Color, _out: &mut Readout) {
+        self.control = self.control.next();
+    }
+}
@@ -0,0 +1,87 @@
+/// Height of every glyph, in pixels
+pub const HEIGHT: usize = 5;
+
+// Compact bitmap font covering printable ASCII from ' ' to 'Z', three columns each, with bit 0 the top row.  Narrow glyphs
+// are packed against the left column.
+const GLYPHS: [[u8; 3]; 59] = [
+    [0x00, 0x00, 0x00], // space
+    [0x17, 0x00, 0x00], // !
+    [0x03, 0x00, 0x03], // "
+    [0x1f, 0x0a, 0x1f], // #
+    [0x12, 0x1f, 0x09], // $
+    [0x19, 0x04, 0x13], // %
+    [0x0a, 0x15, 0x1a], // &
+    [0x03, 0x00, 0x00], // '
+    [0x0e, 0x11, 0x00], // (
+    [0x11, 0x0e, 0x00], // )
+    [0x0a, 0x04, 0x0a], // *
+    [0x04, 0x0e, 0x04], // +
+    [0x10, 0x08, 0x00], // ,
+    [0x04, 0x04, 0x04], // -
+    [0x10, 0x00, 0x00], // .
+    [0x18, 0x04, 0x03], // /
+    [0x1f, 0x11, 0x1f], // 0
+    [0x12, 0x1f, 0x10], // 1
+    [0x19, 0x15, 0x12], // 2
+    [0x11, 0x15, 0x0a], // 3
+    [0x07, 0x04, 0x1f], // 4
+    [0x17, 0x15, 0x09], // 5
+    [0x1e, 0x15, 0x1d], // 6
+    [0x01, 0x1d, 0x03], // 7
+    [0x1f, 0x15, 0x1f], // 8
+    [0x17, 0x15, 0x0f], // 9
+    [0x0a, 0x00, 0x00], // :
+    [0x10, 0x0a, 0x00], // ;
+    [0x04, 0x0a, 0x11], // <
+    [0x0a, 0x0a, 0x0a], // =
+    [0x11, 0x0a, 0x04], // >
+    [0x01, 0x15, 0x02], // ?
+    [0x0e, 0x15, 0x16], // @
+    [0x1e, 0x05, 0x1e], // A
+    [0x1f, 0x15, 0x0a], // B
+    [0x0e, 0x11, 0x11], // C
+    [0x1f, 0x11, 0x0e], // D
+    [0x1f, 0x15, 0x11], // E
+    [0x1f, 0x05, 0x01], // F
+    [0x0e, 0x11, 0x1d], // G
+    [0x1f, 0x04, 0x1f], // H
+    [0x11, 0x1f, 0x11], // I
+    [0x08, 0x10, 0x0f], // J
+    [0x1f, 0x04, 0x1b], // K
+    [0x1f, 0x10, 0x10], // L
+    [0x1f, 0x06, 0x1f], // M
+    [0x1f, 0x01, 0x1e], // N
+    [0x0e, 0x11, 0x0e], // O
+    [0x1f, 0x05, 0x02], // P
+    [0x0e, 0x19, 0x16], // Q
+    [0x1f, 0x05, 0x1a], // R
+    [0x12, 0x15, 0x09], // S
+    [0x01, 0x1f, 0x01], // T
+    [0x1f, 0x10, 0x1f], // U
+    [0x0f, 0x10, 0x0f], // V
+    [0x1f, 0x0c, 0x1f], // W
+    [0x1b, 0x04, 0x1b], // X
+    [0x03, 0x1c, 0x03], // Y
+    [0x19, 0x15, 0x13], // Z
+];
+
+/// Columns of the glyph for `c`.  Lowercase letters are drawn as capitals, and anything else
+/// outside the font as `?`.
+pub fn glyph(c: char) -> &'static [u8] {
+    let c = c.to_ascii_uppercase();
+    let cols = match c {
+        ' '..='Z' => &GLYPHS[c as usize - ' ' as usize],
+        _ => &GLYPHS['?' as usize - ' ' as usize],
+    };
+    // Trim trailing blank columns so narrow glyphs take less room, but keep spaces visible
+    let width = match cols.iter().rposition(|&col| col != 0) {
+        Some(last) => last + 1,
+        None => 2,
+    };
+    &cols[..width]
+}
+
+/// Whether the pixel at `row` of a glyph column is lit
+pub fn lit(col: u8, row: usize) -> bool {
+    col >> row & 1 != 0
+}
@@ -1,9 +1,12 @@
 #![no_std]
+pub mod clock;
+pub use clock::{TimeOfDay, TimeSource};
 pub mod color;
 pub mod effect;
+pub mod font;
 pub use effect::Effect;
 pub mod hsv;
-pub use color::{lch_color, lch_to_rgb, Color};
+pub use color::{blackbody, lch_color, lch_to_rgb, Color};
 pub mod model;
 pub use model::lantern::Lantern;
 pub mod math;
@@ -11,6 +14,8 @@ pub mod pixelindex;
 pub use pixelindex::{FaceType, PixelIndex, PixelIndexable};
 pub mod rotary;
 pub use rotary::{Click, Direction, Rotary};
+pub mod rtc;
+pub use rtc::Rtc;
 pub mod tick;
 pub use tick::Tick;
 
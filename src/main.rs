@@ -10,7 +10,7 @@ use silmaril::{
     effect::*,
     lch_color, Click, InputEvent,
     Knobs::{self, *},
-    Lantern, Rotary,
+    Lantern, Rotary, Rtc, TimeSource,
 };
 use smart_leds::SmartLedsWrite;
 use stm32f4xx_hal::{
@@ -26,7 +26,8 @@ use stm32f4xx_hal::{
     stm32 as pac,
 };
 
-const PERIOD: u32 = 10_000_000;
+const SYSCLK_HZ: u32 = 100_000_000;
+const PERIOD: u32 = SYSCLK_HZ / TICKS_PER_SECOND;
 
 #[app(device = stm32f4xx_hal::stm32, peripherals = true, monotonic = rtic::cyccnt::CYCCNT)]
 const APP: () = {
@@ -43,6 +44,7 @@ const APP: () = {
             >,
         >,
         effect: EffectManager<Lantern>,
+        rtc: Rtc,
         led: PC13<Output<PushPull>>,
         user: PA0<Input<PullUp>>,
         knob1: Rotary<PB13<Input<PullUp>>, PB12<Input<PullUp>>, PB14<Input<PullUp>>>,
@@ -77,8 +79,10 @@ const APP: () = {
         // Required for interrupts on GPIO ports
         dp.RCC.apb2enr.write(|w| w.syscfgen().enabled());
 
+        let rtc = Rtc::new(dp.RTC, &dp.RCC, &dp.PWR);
+
         let rcc = dp.RCC.constrain();
-        let clocks = rcc.cfgr.use_hse(25.mhz()).sysclk(100.mhz()).freeze();
+        let clocks = rcc.cfgr.use_hse(25.mhz()).sysclk(SYSCLK_HZ.hz()).freeze();
         let gpioc = dp.GPIOC.split();
         let mut led = gpioc.pc13.into_push_pull_output();
         let _ = led.set_high();
@@ -167,6 +171,7 @@ const APP: () = {
             model,
             leds,
             effect,
+            rtc,
             led,
             user,
             knob1,
@@ -175,13 +180,18 @@ const APP: () = {
         }
     }
 
-    #[task(resources = [model, leds, effect], schedule = [tick])]
+    #[task(resources = [model, leds, effect, rtc], schedule = [tick])]
     fn tick(cx: tick::Context) {
+        let now = cx.resources.rtc.now();
+        if let Some(time) = cx.resources.effect.sync_time(now) {
+            cx.resources.rtc.set(time);
+        }
         cx.resources.effect.tick();
         let model: &mut Lantern = cx.resources.model;
         cx.resources.effect.render(model);
+        let mut frame = cx.resources.effect.composite(model);
         let mut buf = [[0; 3]; 125];
-        model.render(&mut buf);
+        frame.render(&mut buf);
         let _ = cx.resources.leds.write(buf.iter().cloned());
         cx.schedule.tick(cx.scheduled + PERIOD.cycles()).unwrap();
     }
@@ -1,4 +1,19 @@
 pub mod noise;
+pub mod vec3;
+pub use vec3::Vec3;
+
+use num_traits::Float;
+
+/// Wrap a coordinate into `[0, 1)`, e.g. a direction that has travelled around the sides.
+pub fn wrap(x: f32) -> f32 {
+    x - x.floor()
+}
+
+/// Smooth Hermite interpolation from 0.0 at `edge0` to 1.0 at `edge1`.
+pub fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
 
 // Work around https://github.com/rust-lang/rust/issues/62729
 #[no_mangle]
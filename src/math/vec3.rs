@@ -0,0 +1,97 @@
+use crate::math::wrap;
+use core::{
+    f32::consts::PI,
+    ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
+};
+use num_traits::Float;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Vec3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Vec3 {
+    pub const fn new(x: f32, y: f32, z: f32) -> Self {
+        Self { x, y, z }
+    }
+    pub fn zero() -> Self {
+        Self::new(0.0, 0.0, 0.0)
+    }
+    /// Point on the unit sphere for spherical (dir, height), taking height as elevation from the
+    /// horizon (0.0) to the zenith (1.0).
+    pub fn from_spherical(dir: f32, height: f32) -> Self {
+        let (az_sin, az_cos) = (dir * 2.0 * PI).sin_cos();
+        let (el_sin, el_cos) = (height * PI / 2.0).sin_cos();
+        Self::new(el_cos * az_cos, el_cos * az_sin, el_sin)
+    }
+    /// Inverse of `from_spherical` for a unit vector.
+    pub fn to_spherical(self) -> (f32, f32) {
+        let dir = wrap(self.y.atan2(self.x) / (2.0 * PI));
+        let height = self.z.clamp(-1.0, 1.0).asin() / (PI / 2.0);
+        (dir, height)
+    }
+    pub fn dot(self, rhs: Self) -> f32 {
+        self.x * rhs.x + self.y * rhs.y + self.z * rhs.z
+    }
+    pub fn length_squared(self) -> f32 {
+        self.dot(self)
+    }
+    pub fn length(self) -> f32 {
+        self.length_squared().sqrt()
+    }
+    /// Scale to unit length, leaving a zero vector unchanged.
+    pub fn normalize(self) -> Self {
+        let len = self.length();
+        if len > 0.0 {
+            self * (1.0 / len)
+        } else {
+            self
+        }
+    }
+    /// Remove the component along the unit vector `normal`, leaving a vector tangent to it.
+    pub fn tangent(self, normal: Self) -> Self {
+        self - normal * self.dot(normal)
+    }
+}
+
+impl Add for Vec3 {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+    }
+}
+
+impl AddAssign for Vec3 {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Vec3 {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+    }
+}
+
+impl SubAssign for Vec3 {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl Mul<f32> for Vec3 {
+    type Output = Self;
+    fn mul(self, rhs: f32) -> Self {
+        Self::new(self.x * rhs, self.y * rhs, self.z * rhs)
+    }
+}
+
+impl Neg for Vec3 {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y, -self.z)
+    }
+}
@@ -3,6 +3,7 @@ use num_traits::Float;
 use palette::{Hue, Saturate, Shade};
 use typenum::{U125, U5};
 // XXX TODO Rename to Cube
+#[derive(Clone)]
 pub struct Lantern {
     pub color: Color,
     pub pixels: [Color; 125],
@@ -220,6 +221,22 @@ impl PixelIndexable for Lantern {
             (angle, height)
         }
     }
+    fn index_to_cube_xyz(idx: PixelIndex<Self>) -> (f32, f32, f32) {
+        let i: usize = idx.into();
+        let face_offset = i.rem_euclid(25);
+        // Pixel centres sit 0.4 apart, leaving half a pixel between the outer ones and each edge
+        let coord = |n: usize| (n as f32 - 2.0) * 0.4;
+        let x = face_offset.rem_euclid(5);
+        let y = face_offset / 5;
+        match i / 25 {
+            0 => (coord(x), -1.0, -coord(y)),
+            1 => (1.0, coord(x), -coord(y)),
+            2 => (-coord(x), 1.0, -coord(y)),
+            3 => (-1.0, -coord(x), -coord(y)),
+            // The top face is numbered in columns from the north east corner, see index_above
+            _ => (-coord(face_offset / 5), -coord(face_offset % 5), 1.0),
+        }
+    }
     fn index_to_row_col(_idx: PixelIndex<Self>) -> (usize, usize) {
         todo!()
     }
@@ -91,9 +91,11 @@ pub trait PixelIndexable: Sized {
     /*
     fn index_to_cylindrical(idx: PixelIndex<Self>) -> (f32, f32, f32);
     fn index_to_face_xy(idx: PixelIndex<Self>) -> (Self::Face, f32, f32);
-    fn index_to_cube_xyz(idx: PixelIndex<Self>) -> (f32, f32, f32);
     fn index_to_face_polar(idx: PixelIndex<Self>) -> (Self::Face, f32, f32);
     */
+    /// Position of the pixel's centre on the surface of a cube spanning -1.0 to 1.0 on each axis,
+    /// with z pointing up.
+    fn index_to_cube_xyz(idx: PixelIndex<Self>) -> (f32, f32, f32);
     fn index_to_spherical(idx: PixelIndex<Self>) -> (f32, f32);
     fn index_to_row_col(idx: PixelIndex<Self>) -> (usize, usize);
 
@@ -127,6 +129,9 @@ where
     pub fn as_spherical(self) -> (f32, f32) {
         T::index_to_spherical(self)
     }
+    pub fn as_cube_xyz(self) -> (f32, f32, f32) {
+        T::index_to_cube_xyz(self)
+    }
     pub fn face(self) -> T::Face {
         T::index_to_face(self)
     }
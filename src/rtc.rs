@@ -0,0 +1,118 @@
+use crate::{TimeOfDay, TimeSource};
+use stm32f4xx_hal::stm32::{PWR, RCC, RTC};
+
+// Prescalers dividing the 32.768kHz LSE down to 1Hz
+const PREDIV_A: u8 = 127;
+const PREDIV_S: u16 = 255;
+// Synchronous prescaler for the nominally 32kHz LSI
+const LSI_PREDIV_S: u16 = 249;
+// Polls of LSERDY before giving up on the crystal, a few seconds on the 16MHz HSI
+const LSE_TIMEOUT: u32 = 10_000_000;
+
+/// The STM32F4 real time clock, running from the 32.768kHz LSE crystal.  If the crystal won't
+/// start it falls back to the internal LSI, which keeps time to within a few percent.
+pub struct Rtc {
+    regs: RTC,
+}
+
+impl Rtc {
+    /// Take over the RTC.  This must be called before `RCC` is constrained.  The backup domain
+    /// keeps time across resets, so a clock that is already running is left alone.
+    pub fn new(regs: RTC, rcc: &RCC, pwr: &PWR) -> Self {
+        // Allow access to the backup domain
+        rcc.apb1enr.modify(|_, w| w.pwren().set_bit());
+        pwr.cr.modify(|_, w| w.dbp().set_bit());
+        let mut rtc = Self { regs };
+        if rcc.bdcr.read().rtcen().bit_is_clear() {
+            rcc.bdcr.modify(|_, w| w.lseon().set_bit());
+            let lse_ready = (0..LSE_TIMEOUT).any(|_| rcc.bdcr.read().lserdy().bit_is_set());
+            let prediv_s = if lse_ready {
+                rcc.bdcr.modify(|_, w| w.rtcsel().lse().rtcen().set_bit());
+                PREDIV_S
+            } else {
+                // Missing or faulty crystal; don't hang the lantern waiting for it
+                rcc.bdcr.modify(|_, w| w.lseon().clear_bit());
+                Self::start_lsi(rcc);
+                rcc.bdcr.modify(|_, w| w.rtcsel().lsi().rtcen().set_bit());
+                LSI_PREDIV_S
+            };
+            rtc.configure(|regs| {
+                // The prescalers take two separate writes, synchronous first
+                regs.prer.write(|w| unsafe { w.prediv_s().bits(prediv_s) });
+                regs.prer
+                    .modify(|_, w| unsafe { w.prediv_a().bits(PREDIV_A) });
+                // 24 hour format
+                regs.cr.modify(|_, w| w.fmt().clear_bit());
+            });
+        } else {
+            // Unlike the LSE, the LSI is stopped by a reset and has to be restarted
+            if rcc.bdcr.read().rtcsel().is_lsi() {
+                Self::start_lsi(rcc);
+            }
+            // After a reset the shadow registers may hold a stale time until they resync
+            rtc.wait_for_sync();
+        }
+        rtc
+    }
+
+    fn start_lsi(rcc: &RCC) {
+        rcc.csr.modify(|_, w| w.lsion().set_bit());
+        while rcc.csr.read().lsirdy().bit_is_clear() {}
+    }
+
+    /// Clear RSF and wait for the calendar shadow registers to be refreshed from the clock.
+    fn wait_for_sync(&mut self) {
+        self.regs.wpr.write(|w| unsafe { w.key().bits(0xCA) });
+        self.regs.wpr.write(|w| unsafe { w.key().bits(0x53) });
+        self.regs.isr.modify(|_, w| w.rsf().clear_bit());
+        while self.regs.isr.read().rsf().bit_is_clear() {}
+        self.regs.wpr.write(|w| unsafe { w.key().bits(0xFF) });
+    }
+
+    /// Run `f` with write protection disabled and the calendar stopped in init mode.
+    fn configure<F: FnOnce(&RTC)>(&mut self, f: F) {
+        self.regs.wpr.write(|w| unsafe { w.key().bits(0xCA) });
+        self.regs.wpr.write(|w| unsafe { w.key().bits(0x53) });
+        self.regs.isr.modify(|_, w| w.init().set_bit());
+        while self.regs.isr.read().initf().bit_is_clear() {}
+        f(&self.regs);
+        self.regs.isr.modify(|_, w| w.init().clear_bit());
+        self.regs.wpr.write(|w| unsafe { w.key().bits(0xFF) });
+        // Wait for the shadow registers to pick up the new time
+        self.wait_for_sync();
+    }
+}
+
+impl TimeSource for Rtc {
+    fn now(&mut self) -> TimeOfDay {
+        let tr = self.regs.tr.read();
+        // Reading TR locks the calendar shadow registers until DR is read
+        let _ = self.regs.dr.read();
+        TimeOfDay::new(
+            tr.ht().bits() * 10 + tr.hu().bits(),
+            tr.mnt().bits() * 10 + tr.mnu().bits(),
+            tr.st().bits() * 10 + tr.su().bits(),
+        )
+    }
+
+    fn set(&mut self, time: TimeOfDay) {
+        self.configure(|regs| {
+            regs.tr.write(|w| unsafe {
+                w.pm()
+                    .clear_bit()
+                    .ht()
+                    .bits(time.hours / 10)
+                    .hu()
+                    .bits(time.hours % 10)
+                    .mnt()
+                    .bits(time.minutes / 10)
+                    .mnu()
+                    .bits(time.minutes % 10)
+                    .st()
+                    .bits(time.seconds / 10)
+                    .su()
+                    .bits(time.seconds % 10)
+            });
+        });
+    }
+}